@@ -1,6 +1,6 @@
 use node_template_runtime::{
 	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, Signature, SudoConfig,
-	SystemConfig, WASM_BINARY,
+	SystemConfig, TemplateModuleConfig, WASM_BINARY,
 };
 use sc_service::ChainType;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
@@ -31,6 +31,23 @@ where
 	AccountPublic::from(get_from_seed::<TPublic>(seed)).into_account()
 }
 
+/// The `next_light_client_block` response the development chains anchor the NEAR light client at.
+const NEAR_TRUST_ANCHOR: &[u8] =
+	include_bytes!("../../pallets/template/fixtures/1_current_epoch.json");
+
+/// Generate the NEAR light client trust anchor, from the `next_light_client_block` JSON-RPC
+/// response at the path in `NEAR_TRUST_ANCHOR` if it is set.
+pub fn near_trust_anchor() -> Result<TemplateModuleConfig, String> {
+	match std::env::var_os("NEAR_TRUST_ANCHOR") {
+		Some(path) => {
+			let json = std::fs::read(&path)
+				.map_err(|e| format!("Failed to read {}: {}", path.to_string_lossy(), e))?;
+			TemplateModuleConfig::from_json_rpc(&json)
+		},
+		None => TemplateModuleConfig::from_json_rpc(NEAR_TRUST_ANCHOR),
+	}
+}
+
 /// Generate an Aura authority key.
 pub fn authority_keys_from_seed(s: &str) -> (AuraId, GrandpaId) {
 	(get_from_seed::<AuraId>(s), get_from_seed::<GrandpaId>(s))
@@ -38,6 +55,7 @@ pub fn authority_keys_from_seed(s: &str) -> (AuraId, GrandpaId) {
 
 pub fn development_config() -> Result<ChainSpec, String> {
 	let wasm_binary = WASM_BINARY.ok_or_else(|| "Development wasm not available".to_string())?;
+	let trust_anchor = near_trust_anchor()?;
 
 	Ok(ChainSpec::from_genesis(
 		// Name
@@ -59,6 +77,9 @@ pub fn development_config() -> Result<ChainSpec, String> {
 					get_account_id_from_seed::<sr25519::Public>("Alice//stash"),
 					get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
 				],
+				// NEAR relayers
+				vec![get_account_id_from_seed::<sr25519::Public>("Alice")],
+				trust_anchor.clone(),
 				true,
 			)
 		},
@@ -78,6 +99,7 @@ pub fn development_config() -> Result<ChainSpec, String> {
 
 pub fn local_testnet_config() -> Result<ChainSpec, String> {
 	let wasm_binary = WASM_BINARY.ok_or_else(|| "Development wasm not available".to_string())?;
+	let trust_anchor = near_trust_anchor()?;
 
 	Ok(ChainSpec::from_genesis(
 		// Name
@@ -107,6 +129,12 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 					get_account_id_from_seed::<sr25519::Public>("Eve//stash"),
 					get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
 				],
+				// NEAR relayers
+				vec![
+					get_account_id_from_seed::<sr25519::Public>("Alice"),
+					get_account_id_from_seed::<sr25519::Public>("Bob"),
+				],
+				trust_anchor.clone(),
 				true,
 			)
 		},
@@ -130,6 +158,8 @@ fn testnet_genesis(
 	initial_authorities: Vec<(AuraId, GrandpaId)>,
 	root_key: AccountId,
 	endowed_accounts: Vec<AccountId>,
+	near_relayers: Vec<AccountId>,
+	near_trust_anchor: TemplateModuleConfig,
 	_enable_println: bool,
 ) -> GenesisConfig {
 	GenesisConfig {
@@ -152,5 +182,6 @@ fn testnet_genesis(
 			key: Some(root_key),
 		},
 		transaction_payment: Default::default(),
+		template_module: TemplateModuleConfig { relayers: near_relayers, ..near_trust_anchor },
 	}
 }
@@ -0,0 +1,139 @@
+//! The `near_*` RPC methods, backed by the `NearLightClientApi` runtime API.
+//!
+//! Parameters and results use the JSON shapes of the NEAR RPC: base58 hashes and keys, and
+//! balances and nanosecond timestamps as decimal strings.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use jsonrpsee::{
+	core::RpcResult,
+	proc_macros::rpc,
+	types::error::{CallError, ErrorObject},
+};
+use node_template_runtime::pallet_template::{
+	near::{
+		hash::CryptoHash,
+		proof::RpcLightClientExecutionProofResponse,
+		views::{LightClientBlockLiteView, ValidatorStakeView},
+	},
+	runtime_api::NearLightClientApi,
+	traits::VerifiedOutcome,
+};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::{Block as BlockT, NumberFor};
+
+/// Error code of failed runtime API calls.
+const RUNTIME_ERROR: i32 = 1;
+/// Error code of proofs rejected by the light client.
+const INVALID_PROOF: i32 = 2;
+
+/// NEAR light client RPC methods.
+#[rpc(server)]
+pub trait NearApi<BlockHash, BlockNumber> {
+	/// The trusted head of the light client.
+	#[method(name = "near_head")]
+	fn head(&self, at: Option<BlockHash>) -> RpcResult<Option<LightClientBlockLiteView>>;
+
+	/// The block producers stored for `epoch`.
+	#[method(name = "near_producers")]
+	fn producers(
+		&self,
+		epoch: CryptoHash,
+		at: Option<BlockHash>,
+	) -> RpcResult<Option<Vec<ValidatorStakeView>>>;
+
+	/// Verify an `EXPERIMENTAL_light_client_proof` response against the trusted head, without
+	/// submitting it.
+	#[method(name = "near_verifyProof")]
+	fn verify_proof(
+		&self,
+		proof: RpcLightClientExecutionProofResponse,
+		at: Option<BlockHash>,
+	) -> RpcResult<VerifiedOutcome<BlockNumber>>;
+
+	/// Whether the outcome of the transaction or receipt `id` was verified on chain.
+	#[method(name = "near_isOutcomeVerified")]
+	fn is_outcome_verified(&self, id: CryptoHash, at: Option<BlockHash>) -> RpcResult<bool>;
+}
+
+/// Implements the [`NearApiServer`] RPC trait with the light client runtime API.
+pub struct Near<C, Block> {
+	client: Arc<C>,
+	_marker: PhantomData<Block>,
+}
+
+impl<C, Block> Near<C, Block> {
+	/// Create new `Near` with the given reference to the client.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client, _marker: Default::default() }
+	}
+}
+
+impl<C, Block> Near<C, Block>
+where
+	Block: BlockT,
+	C: HeaderBackend<Block>,
+{
+	fn at(&self, at: Option<Block::Hash>) -> Block::Hash {
+		at.unwrap_or_else(|| self.client.info().best_hash)
+	}
+}
+
+fn runtime_error(e: impl std::fmt::Display) -> jsonrpsee::core::Error {
+	CallError::Custom(ErrorObject::owned(
+		RUNTIME_ERROR,
+		"Unable to query the NEAR light client.",
+		Some(e.to_string()),
+	))
+	.into()
+}
+
+impl<C, Block> NearApiServer<Block::Hash, NumberFor<Block>> for Near<C, Block>
+where
+	Block: BlockT,
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: NearLightClientApi<Block, NumberFor<Block>>,
+{
+	fn head(&self, at: Option<Block::Hash>) -> RpcResult<Option<LightClientBlockLiteView>> {
+		self.client.runtime_api().head(self.at(at)).map_err(runtime_error)
+	}
+
+	fn producers(
+		&self,
+		epoch: CryptoHash,
+		at: Option<Block::Hash>,
+	) -> RpcResult<Option<Vec<ValidatorStakeView>>> {
+		self.client
+			.runtime_api()
+			.block_producers(self.at(at), epoch)
+			.map_err(runtime_error)
+	}
+
+	fn verify_proof(
+		&self,
+		proof: RpcLightClientExecutionProofResponse,
+		at: Option<Block::Hash>,
+	) -> RpcResult<VerifiedOutcome<NumberFor<Block>>> {
+		self.client
+			.runtime_api()
+			.verify_execution_proof(self.at(at), proof)
+			.map_err(runtime_error)?
+			.map_err(|e| {
+				CallError::Custom(ErrorObject::owned(
+					INVALID_PROOF,
+					"The proof was rejected.",
+					Some(e.to_string()),
+				))
+				.into()
+			})
+	}
+
+	fn is_outcome_verified(&self, id: CryptoHash, at: Option<Block::Hash>) -> RpcResult<bool> {
+		self.client
+			.runtime_api()
+			.verified_outcome(self.at(at), id)
+			.map(|outcome| outcome.is_some())
+			.map_err(runtime_error)
+	}
+}
@@ -10,7 +10,10 @@ pub use pallet::*;
 mod mock;
 
 pub mod crypto;
-mod near;
+pub mod migrations;
+pub mod near;
+pub mod runtime_api;
+pub mod traits;
 
 #[cfg(test)]
 mod tests;
@@ -18,34 +21,209 @@ mod tests;
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
 
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_system::offchain::{SignedPayload, SigningTypes};
+use near::{
+	hash::CryptoHash,
+	proof::{RpcLightClientExecutionProofResponse, TransactionOrReceiptId},
+	views::LightClientBlockView,
+};
+use sp_runtime::{sp_std::prelude::*, RuntimeDebug};
+
+/// Payload used by offchain workers to submit light client blocks.
+///
+/// It is signed by one of the worker's `crypto::KEY_TYPE` keys so that unsigned submissions
+/// can be told apart from arbitrary transactions in the pool.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, scale_info::TypeInfo)]
+pub struct BlockViewPayload<Public> {
+	pub block_view: LightClientBlockView,
+	pub public: Public,
+}
+
+impl<T: SigningTypes> SignedPayload<T> for BlockViewPayload<T::Public> {
+	fn public(&self) -> T::Public {
+		self.public.clone()
+	}
+}
+
+/// Payload used by offchain workers to submit a chain of light client blocks at once, to catch
+/// up with NEAR.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, scale_info::TypeInfo)]
+pub struct HeadersPayload<Public> {
+	/// The blocks, each following the one before it.
+	pub block_views: Vec<LightClientBlockView>,
+	pub public: Public,
+}
+
+impl<T: SigningTypes> SignedPayload<T> for HeadersPayload<T::Public> {
+	fn public(&self) -> T::Public {
+		self.public.clone()
+	}
+}
+
+/// Payload used by offchain workers to submit execution outcome proofs.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, scale_info::TypeInfo)]
+pub struct ExecutionProofPayload<Public> {
+	pub proof: RpcLightClientExecutionProofResponse,
+	pub public: Public,
+}
+
+impl<T: SigningTypes> SignedPayload<T> for ExecutionProofPayload<T::Public> {
+	fn public(&self) -> T::Public {
+		self.public.clone()
+	}
+}
+
+/// How an offchain worker submits the light client blocks it fetched.
+///
+/// Set as a JSON string, e.g. `"signed"`, under the `near::relayer-mode` offchain local storage
+/// key. Workers submit unsigned transactions by default.
+#[derive(Clone, Copy, Default, PartialEq, Eq, RuntimeDebug, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RelayerMode {
+	/// Submit with `submit`, or `submit_headers` to catch up, signed by the worker key but free
+	/// of fees.
+	#[default]
+	Unsigned,
+	/// Submit with `relay`, from the account of the worker key. It pays the fees, which are
+	/// refunded along with a reward for each block that advances the head. The worker relays
+	/// one block per run.
+	Signed,
+}
+
+/// A request for the offchain workers to prove the outcome of a NEAR transaction or receipt.
+#[derive(
+	Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, scale_info::TypeInfo, MaxEncodedLen,
+)]
+pub struct ProofRequest<AccountId, Balance, BlockNumber> {
+	/// The account that asked for the proof.
+	pub requester: AccountId,
+	/// The account the verified outcome is for, passed to `OnOutcomeVerified`. The requester,
+	/// unless it named another one.
+	pub target: AccountId,
+	/// The transaction or receipt to prove.
+	pub id: TransactionOrReceiptId,
+	/// The deposit reserved from the requester until the request is fulfilled or expires, zero
+	/// if it was made without one.
+	pub deposit: Balance,
+	/// The block the request was made at.
+	pub requested_at: BlockNumber,
+}
+
+/// What is kept of a past light client head, to prove against recent history.
+#[derive(
+	Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, scale_info::TypeInfo, MaxEncodedLen,
+)]
+pub struct RecentHead {
+	pub hash: CryptoHash,
+	pub outcome_root: CryptoHash,
+	pub block_merkle_root: CryptoHash,
+}
+
 #[frame_support::pallet]
 pub mod pallet {
-	use crate::near::{
-		client::NearRpcClient,
-		hash::CryptoHash,
-		types::BlockHeight,
-		views::{LightClientBlockLiteView, ValidatorStakeView, ValidatorStakeViewScaleHax},
-		LightClientState,
+	use crate::{
+		near::{
+			check_claimed_stake,
+			client::{
+				LightClientProofParams, NearRpcClient, LOCK_BLOCK_EXPIRATION,
+				LOCK_TIMEOUT_EXPIRATION,
+			},
+			errors::{LightClientError, NearErrorCause, ProofError},
+			hash::CryptoHash,
+			proof::{
+				verify_execution_proof, ExecutionOutcomeView, ExecutionStatusView,
+				RpcLightClientExecutionProofResponse, TransactionOrReceiptId,
+			},
+			types::BlockHeight,
+			views::{LightClientBlockLiteView, LightClientBlockView, ValidatorStakeView},
+			HeadUpdate, LightClientState,
+		},
+		runtime_api::ExecutionProofError,
+		traits::{NearOutcomeProvider, OnOutcomeVerified, VerifiedOutcome},
+		BlockViewPayload, ExecutionProofPayload, HeadersPayload, ProofRequest, RecentHead,
+		RelayerMode,
 	};
 	use borsh::maybestd::format;
-	use frame_support::pallet_prelude::*;
+	use frame_support::{
+		dispatch::{DispatchResultWithPostInfo, Pays},
+		pallet_prelude::*,
+		traits::{BalanceStatus, Currency, ExistenceRequirement, ReservableCurrency},
+	};
 	use frame_system::{
 		offchain::{
 			AppCrypto, CreateSignedTransaction, SendSignedTransaction, SendUnsignedTransaction,
-			Signer,
+			SignedPayload, Signer,
 		},
 		pallet_prelude::*,
 	};
 	use sp_runtime::{
+		offchain::{
+			storage::StorageValueRef,
+			storage_lock::{BlockAndTime, StorageLock, StorageLockGuard},
+			Duration, StorageKind,
+		},
 		sp_std::{prelude::*, vec},
-		DispatchResult,
+		traits::{IdentifyAccount, One, Saturating, UniqueSaturatedInto, Zero},
+		DispatchResult, RuntimeAppPublic,
 	};
 
 	pub const MAX_BLOCK_PRODUCERS: u32 = 1024;
+	/// `ref_time` of verifying one ed25519 approval on the reference hardware, rounded up.
+	pub const APPROVAL_WEIGHT: u64 = 50_000_000;
+	/// `ref_time` of decoding, hashing and summing the stake of one block producer.
+	pub const BLOCK_PRODUCER_WEIGHT: u64 = 2_000_000;
+	/// `ref_time` of combining two merkle nodes into their parent.
+	pub const MERKLE_NODE_WEIGHT: u64 = 1_000_000;
+	/// `ref_time` of decoding and hashing one byte of an execution outcome.
+	pub const OUTCOME_BYTE_WEIGHT: u64 = 5_000;
+	/// `ref_time` of decoding one queued proof request and comparing its outcome id.
+	pub const PROOF_REQUEST_WEIGHT: u64 = 200_000;
+	/// The most epochs block producers are stored for. `on_idle` normally prunes them down to
+	/// `EpochRetention` long before, otherwise the oldest are pruned as new ones are stored.
+	pub const MAX_STORED_EPOCHS: u32 = 64;
+	/// Offchain local storage key of the block until which the worker backs off from the NEAR
+	/// RPC after a timeout, and the number of timeouts in a row.
+	const RPC_BACKOFF_KEY: &[u8] = b"near::rpc-backoff";
+	/// The worker backs off for at most `2^MAX_BACKOFF_EXPONENT` blocks.
+	const MAX_BACKOFF_EXPONENT: u32 = 5;
+	/// Offchain local storage key of the outcomes the worker should prove, as a JSON list of
+	/// `LightClientProofParams`. Operators can add to it with the `offchain_localStorageSet` RPC.
+	pub const PROOF_REQUESTS_KEY: &[u8] = b"near::proof-requests";
+	/// Offchain local storage key of the index of the next proof request the worker processes.
+	const PROOF_REQUEST_CURSOR_KEY: &[u8] = b"near::proof-request-cursor";
+	/// Offchain local storage key set when the worker submitted proofs instead of syncing the
+	/// head.
+	const SYNC_DEFERRED_KEY: &[u8] = b"near::sync-deferred";
+	/// Offchain local storage key of the `RelayerMode` of the worker.
+	pub const RELAYER_MODE_KEY: &[u8] = b"near::relayer-mode";
+	/// `InvalidTransaction::Custom` code of blocks submitted out of the relayer's turn.
+	pub const NOT_RELAYER_TURN: u8 = 1;
+	/// `InvalidTransaction::Custom` code of blocks that can not follow the head, see
+	/// `check_claimed_stake`.
+	pub const INVALID_BLOCK: u8 = 2;
+	/// Offchain local storage key of the lock held by the worker while it syncs the head.
+	const SYNC_LOCK_KEY: &[u8] = b"near::sync-lock";
+	/// Offchain local storage key of the height of the last block the worker submitted, and the
+	/// block it was submitted at.
+	const LAST_SUBMITTED_KEY: &[u8] = b"near::last-submitted";
+	/// The number of blocks a submitted block stays in the transaction pool.
+	const SUBMISSION_LONGEVITY: u64 = 5;
+
+	pub type BalanceOf<T> =
+		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+	pub type ProofRequestOf<T> = ProofRequest<
+		<T as frame_system::Config>::AccountId,
+		BalanceOf<T>,
+		<T as frame_system::Config>::BlockNumber,
+	>;
+
+	/// The in-code storage version.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
 
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
-	#[pallet::without_storage_info]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	/// Configure the pallet by specifying the parameters and types on which it depends.
@@ -58,6 +236,79 @@ pub mod pallet {
 		type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
 
 		type Call: From<Call<Self>>;
+
+		/// A configuration for base priority of unsigned transactions.
+		///
+		/// The priority of a submitted block is raised by how far it advances the head.
+		#[pallet::constant]
+		type UnsignedPriority: Get<TransactionPriority>;
+
+		/// NEAR RPC endpoints for recent data, in order of preference.
+		///
+		/// Overridden by the `near::rpc-endpoints` offchain local storage key.
+		type RpcEndpoints: Get<&'static [&'static str]>;
+
+		/// NEAR archival RPC endpoints for historical data, in order of preference.
+		///
+		/// Overridden by the `near::archival-endpoints` offchain local storage key.
+		type ArchivalEndpoints: Get<&'static [&'static str]>;
+
+		/// The currency proof request deposits are reserved in.
+		type Currency: ReservableCurrency<Self::AccountId>;
+
+		/// The least deposit a proof request can be made with, if it is made with one.
+		#[pallet::constant]
+		type ProofRequestDeposit: Get<BalanceOf<Self>>;
+
+		/// The maximum number of pending proof requests.
+		#[pallet::constant]
+		type MaxProofRequests: Get<u32>;
+
+		/// The number of proof requests an offchain worker processes per block.
+		#[pallet::constant]
+		type ProofRequestsPerBlock: Get<u32>;
+
+		/// The number of blocks after which an unfulfilled proof request expires.
+		#[pallet::constant]
+		type ProofRequestTtl: Get<Self::BlockNumber>;
+
+		/// Called when a requested outcome is verified.
+		type OnOutcomeVerified: OnOutcomeVerified<Self::AccountId, Self::BlockNumber>;
+
+		/// The origin that can re-anchor, pause and resume the light client.
+		type AdminOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// The number of most recent epochs whose block producers are kept. At least 2, for the
+		/// epoch of the head and the next one.
+		#[pallet::constant]
+		type EpochRetention: Get<u32>;
+
+		/// The number of most recent light client heads kept in `RecentHeads`.
+		#[pallet::constant]
+		type HeadHistory: Get<u32>;
+
+		/// The account relayer rewards are paid from.
+		type RewardPot: Get<Self::AccountId>;
+
+		/// The reward for relaying a block that advances the head.
+		#[pallet::constant]
+		type HeaderReward: Get<BalanceOf<Self>>;
+
+		/// The additional reward for relaying the first block of an epoch.
+		#[pallet::constant]
+		type EpochReward: Get<BalanceOf<Self>>;
+
+		/// The maximum number of registered relayers.
+		#[pallet::constant]
+		type MaxRelayers: Get<u32>;
+
+		/// The number of blocks each relayer in turn is assigned to submit light client blocks.
+		#[pallet::constant]
+		type RelayerTurn: Get<Self::BlockNumber>;
+
+		/// The most light client blocks submitted at once with `submit_headers`.
+		#[pallet::constant]
+		type MaxHeadersPerBatch: Get<u32>;
 	}
 
 	#[pallet::storage]
@@ -70,9 +321,140 @@ pub mod pallet {
 		_,
 		Identity,
 		CryptoHash,
-		BoundedVec<ValidatorStakeViewScaleHax, ConstU32<MAX_BLOCK_PRODUCERS>>,
+		BoundedVec<ValidatorStakeView, ConstU32<MAX_BLOCK_PRODUCERS>>,
 	>;
 
+	/// NEAR transaction and receipt outcomes that were proven to be part of the chain, by id.
+	#[pallet::storage]
+	pub type VerifiedOutcomes<T: Config> =
+		StorageMap<_, Identity, CryptoHash, VerifiedOutcome<T::BlockNumber>>;
+
+	/// Whether the light client is paused, rejecting blocks and execution proofs.
+	#[pallet::storage]
+	pub type Paused<T> = StorageValue<_, bool, ValueQuery>;
+
+	/// Pending proof requests, oldest first.
+	#[pallet::storage]
+	pub type ProofRequests<T: Config> =
+		StorageValue<_, BoundedVec<ProofRequestOf<T>, T::MaxProofRequests>, ValueQuery>;
+
+	/// The epochs in `BlockProducersByEpoch`, oldest first.
+	#[pallet::storage]
+	pub type StoredEpochs<T> =
+		StorageValue<_, BoundedVec<CryptoHash, ConstU32<MAX_STORED_EPOCHS>>, ValueQuery>;
+
+	/// The last `HeadHistory` light client heads, by height.
+	#[pallet::storage]
+	#[pallet::getter(fn recent_head)]
+	pub type RecentHeads<T> = StorageMap<_, Twox64Concat, BlockHeight, RecentHead>;
+
+	/// The heights in `RecentHeads`, oldest first.
+	#[pallet::storage]
+	pub type RecentHeights<T: Config> =
+		StorageValue<_, BoundedVec<BlockHeight, T::HeadHistory>, ValueQuery>;
+
+	/// The accounts allowed to submit light client blocks, taking turns of `RelayerTurn` blocks
+	/// in this order.
+	#[pallet::storage]
+	#[pallet::getter(fn relayers)]
+	pub type Relayers<T: Config> =
+		StorageValue<_, BoundedVec<T::AccountId, T::MaxRelayers>, ValueQuery>;
+
+	/// The trust anchor of the light client: the head it starts from, and the block producers
+	/// that sign the blocks after it.
+	///
+	/// Only the producers of the head's next epoch can be checked against the head, so the head
+	/// must be the last block of its epoch, as `next_light_client_block` returns for every epoch
+	/// but the current one. Blocks after any other head are signed by producers that are not
+	/// known until they are reanchored.
+	#[pallet::genesis_config]
+	#[derive(Clone)]
+	pub struct GenesisConfig<T: Config> {
+		pub head: Option<LightClientBlockLiteView>,
+		/// The epoch `block_producers` produce blocks in, which is the head's next epoch.
+		pub epoch_id: CryptoHash,
+		pub block_producers: Vec<ValidatorStakeView>,
+		/// The initial relayers.
+		pub relayers: Vec<T::AccountId>,
+		#[serde(skip)]
+		pub _config: PhantomData<T>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self {
+				head: None,
+				epoch_id: CryptoHash::default(),
+				block_producers: Vec::new(),
+				relayers: Vec::new(),
+				_config: PhantomData,
+			}
+		}
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> GenesisConfig<T> {
+		/// Anchor the light client at `block`, trusting the producers of its next epoch.
+		///
+		/// `block` must be the last block of its epoch, see [`GenesisConfig`].
+		pub fn from_light_client_block(block: LightClientBlockView) -> Self {
+			Self {
+				epoch_id: block.inner_lite.next_epoch_id,
+				block_producers: block.next_bps.clone().unwrap_or_default(),
+				head: Some(block.into()),
+				relayers: Vec::new(),
+				_config: PhantomData,
+			}
+		}
+
+		/// Anchor the light client at the block of a `next_light_client_block` JSON-RPC
+		/// response.
+		pub fn from_json_rpc(json: &[u8]) -> Result<Self, String> {
+			use crate::near::client::{JsonRpcResult, NearRpcResult};
+
+			let response: JsonRpcResult = serde_json::from_slice(json)
+				.map_err(|e| format!("Invalid light client block: {}", e))?;
+			match response.result {
+				NearRpcResult::NextBlock(block) if block.next_bps.is_some() =>
+					Ok(Self::from_light_client_block(block)),
+				_ => Err("Expected a light client block with next block producers".into()),
+			}
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			if let Some(head) = &self.head {
+				assert!(
+					self.epoch_id == head.inner_lite.next_epoch_id,
+					"Block producers are not of the head's next epoch"
+				);
+				assert!(
+					Pallet::<T>::matches_next_bp_hash(head, &self.block_producers),
+					"Block producers do not match the head's next_bp_hash"
+				);
+				Pallet::<T>::set_head(head.clone());
+			} else {
+				assert!(
+					self.block_producers.is_empty(),
+					"Block producers can not be checked without a head"
+				);
+			}
+
+			if !self.block_producers.is_empty() {
+				let block_producers = BoundedVec::try_from(self.block_producers.clone())
+					.expect("At most MAX_BLOCK_PRODUCERS block producers");
+				Pallet::<T>::store_block_producers(self.epoch_id, block_producers);
+			}
+
+			for relayer in &self.relayers {
+				Relayers::<T>::try_append(relayer.clone()).expect("At most MaxRelayers relayers");
+			}
+		}
+	}
+
 	// Pallets use events to inform users when important changes are made.
 	// https://docs.substrate.io/main-docs/build/events-errors/
 	#[pallet::event]
@@ -81,6 +463,40 @@ pub mod pallet {
 		/// Event documentation should end with an array that provides descriptive names for event
 		/// parameters. [something, who]
 		SomethingStored { something: u32, who: T::AccountId },
+		/// The light client head was updated. [height, hash]
+		HeadUpdated { height: BlockHeight, hash: CryptoHash },
+		/// A light client block was rejected by the light client. [height, reason]
+		HeaderRejected { height: BlockHeight, reason: LightClientError },
+		/// A transaction or receipt outcome was proven to be part of the NEAR chain.
+		/// [id, block_hash, outcome]
+		ExecutionOutcomeVerified {
+			id: CryptoHash,
+			block_hash: CryptoHash,
+			outcome: ExecutionOutcomeView,
+		},
+		/// The proof of an outcome was requested for `target`. [who, id, target, deposit]
+		ProofRequested {
+			who: T::AccountId,
+			id: CryptoHash,
+			target: T::AccountId,
+			deposit: BalanceOf<T>,
+		},
+		/// A requested outcome was verified. [who, id, target]
+		ProofRequestFulfilled { who: T::AccountId, id: CryptoHash, target: T::AccountId },
+		/// A requested outcome was not verified in time, and the deposit was forfeited. [who, id]
+		ProofRequestExpired { who: T::AccountId, id: CryptoHash },
+		/// The light client was re-anchored at a new head. [previous, height, hash]
+		Reanchored { previous: Option<CryptoHash>, height: BlockHeight, hash: CryptoHash },
+		/// The light client stopped accepting blocks and execution proofs.
+		LightClientPaused,
+		/// The light client accepts blocks and execution proofs again.
+		LightClientResumed,
+		/// A relayer was paid for advancing the head. [who, amount]
+		RelayerRewarded { who: T::AccountId, amount: BalanceOf<T> },
+		/// A relayer was allowed to submit light client blocks. [who]
+		RelayerRegistered { who: T::AccountId },
+		/// A relayer was removed from the relayers. [who]
+		RelayerDeregistered { who: T::AccountId },
 	}
 
 	// Errors inform users that something went wrong.
@@ -90,6 +506,93 @@ pub mod pallet {
 		NoneValue,
 		/// Errors should have helpful documentation associated with them.
 		StorageOverflow,
+		/// The block is not newer than the light client head.
+		BlockAlreadyVerified,
+		/// The block is neither in the epoch of the head nor in the next one.
+		InvalidEpoch,
+		/// The block is in the next epoch but does not carry the next block producers.
+		NextBlockProducersMissing,
+		/// An approval was not signed by the corresponding block producer.
+		InvalidSignature,
+		/// The approved stake does not exceed two thirds of the total stake.
+		InsufficientApprovedStake,
+		/// The next block producers do not hash to the block's `next_bp_hash`.
+		InvalidNextBlockProducersHash,
+		/// There is no trusted light client head to verify against.
+		NoTrustedHead,
+		/// The block producers of the block's epoch are unknown.
+		UnknownBlockProducers,
+		/// The block carries more than `MAX_BLOCK_PRODUCERS` block producers.
+		TooManyBlockProducers,
+		/// The outcome does not hash up to the outcome root of the block it claims to be in.
+		InvalidOutcomeRoot,
+		/// The block of the outcome is not known to the light client head.
+		InvalidBlockProof,
+		/// The outcome was already verified.
+		OutcomeAlreadyVerified,
+		/// The proof of the outcome was already requested.
+		OutcomeAlreadyRequested,
+		/// There are `MaxProofRequests` pending proof requests already.
+		TooManyProofRequests,
+		/// The light client is paused.
+		Paused,
+		/// The block has fewer approvals than there are block producers.
+		MissingApprovals,
+		/// The stake of the block producers overflows.
+		StakeOverflow,
+		/// The account is not a registered relayer.
+		NotRelayer,
+		/// The relayer is registered already.
+		AlreadyRelayer,
+		/// There are `MaxRelayers` relayers already.
+		TooManyRelayers,
+		/// It is another relayer's turn to submit light client blocks.
+		NotRelayerTurn,
+		/// More than `MaxHeadersPerBatch` blocks were submitted at once.
+		TooManyHeaders,
+		/// The block producers are not of the head's next epoch, so they can not be checked
+		/// against it.
+		UnverifiableBlockProducers,
+		/// The deposit of the proof request is less than `ProofRequestDeposit`.
+		DepositTooLow,
+	}
+
+	impl<T> From<LightClientError> for Error<T> {
+		fn from(err: LightClientError) -> Self {
+			match err {
+				LightClientError::BlockAlreadyVerified { .. } => Error::<T>::BlockAlreadyVerified,
+				LightClientError::InvalidEpoch => Error::<T>::InvalidEpoch,
+				LightClientError::NextBlockProducersMissing =>
+					Error::<T>::NextBlockProducersMissing,
+				LightClientError::InvalidSignature { .. } => Error::<T>::InvalidSignature,
+				LightClientError::InsufficientApprovedStake { .. } =>
+					Error::<T>::InsufficientApprovedStake,
+				LightClientError::InvalidNextBlockProducersHash =>
+					Error::<T>::InvalidNextBlockProducersHash,
+				LightClientError::MissingApprovals { .. } => Error::<T>::MissingApprovals,
+				LightClientError::StakeOverflow => Error::<T>::StakeOverflow,
+			}
+		}
+	}
+
+	impl<T> From<ProofError> for Error<T> {
+		fn from(err: ProofError) -> Self {
+			match err {
+				ProofError::InvalidOutcomeRoot { .. } => Error::<T>::InvalidOutcomeRoot,
+				ProofError::InvalidBlockProof { .. } => Error::<T>::InvalidBlockProof,
+			}
+		}
+	}
+
+	impl<T> From<ExecutionProofError> for Error<T> {
+		fn from(err: ExecutionProofError) -> Self {
+			match err {
+				ExecutionProofError::Paused => Error::<T>::Paused,
+				ExecutionProofError::NoTrustedHead => Error::<T>::NoTrustedHead,
+				ExecutionProofError::AlreadyVerified => Error::<T>::OutcomeAlreadyVerified,
+				ExecutionProofError::InvalidProof(e) => e.into(),
+			}
+		}
 	}
 
 	#[pallet::validate_unsigned]
@@ -102,32 +605,134 @@ pub mod pallet {
 		/// here we make sure that some particular calls (the ones produced by offchain worker)
 		/// are being whitelisted and marked as valid.
 		fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+			if Paused::<T>::get() {
+				return InvalidTransaction::Call.into()
+			}
+
 			// Firstly let's check that we call the right function.
-			if let Call::submit { head, bps } = call {
-				// let signature_valid =
-				// 	SignedPayload::<T>::verify::<T::AuthorityId>(payload, signature.clone());
-				// if !signature_valid {
-				// 	return InvalidTransaction::BadProof.into()
-				// }
-				// Self::validate_transaction_parameters(&payload.block_number, &payload.price)
-				Ok(ValidTransaction {
-					priority: 1,
-					requires: sp_runtime::sp_std::vec![],
-					provides: sp_runtime::sp_std::vec![],
-					longevity: 1,
-					propagate: false,
-				})
-			} else {
-				InvalidTransaction::Call.into()
+			match call {
+				Call::submit { payload, signature } => {
+					let signature_valid =
+						SignedPayload::<T>::verify::<T::AuthorityId>(payload, signature.clone());
+					if !signature_valid {
+						return InvalidTransaction::BadProof.into()
+					}
+					Self::validate_relayer(&payload.public)?;
+					Self::precheck_block(&payload.block_view)?;
+					Self::validate_transaction_parameters(&payload.block_view)
+				},
+				Call::submit_headers { payload, signature } => {
+					let signature_valid =
+						SignedPayload::<T>::verify::<T::AuthorityId>(payload, signature.clone());
+					if !signature_valid {
+						return InvalidTransaction::BadProof.into()
+					}
+					Self::validate_relayer(&payload.public)?;
+					if payload.block_views.len() > T::MaxHeadersPerBatch::get() as usize {
+						return InvalidTransaction::ExhaustsResources.into()
+					}
+					let (first, last) =
+						match (payload.block_views.first(), payload.block_views.last()) {
+							(Some(first), Some(last)) => (first, last),
+							_ => return InvalidTransaction::Call.into(),
+						};
+					// The batch is stopped at the first block that fails, so it is only as
+					// fresh as its first block, but as useful as its last. Only the first block
+					// follows the current head.
+					Self::precheck_block(first)?;
+					Self::validate_transaction_parameters(first)?;
+					Self::validate_transaction_parameters(last)
+				},
+				Call::submit_execution_proof { payload, signature } => {
+					let signature_valid =
+						SignedPayload::<T>::verify::<T::AuthorityId>(payload, signature.clone());
+					if !signature_valid {
+						return InvalidTransaction::BadProof.into()
+					}
+					Self::validate_relayer(&payload.public)?;
+					Self::validate_execution_proof(&payload.proof)
+				},
+				_ => InvalidTransaction::Call.into(),
 			}
-			// else if let Call::submit_price_unsigned { block_number, price: new_price } = call {
-			// 	Self::validate_transaction_parameters(block_number, new_price)
-			// }
 		}
 	}
 
 	#[pallet::hooks]
 	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		/// Expire the proof requests older than `ProofRequestTtl`.
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			let requests = ProofRequests::<T>::get();
+			let expired = requests
+				.iter()
+				.take_while(|request| {
+					now >= request.requested_at.saturating_add(T::ProofRequestTtl::get())
+				})
+				.count();
+			if expired == 0 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			let mut expired_requests = requests.into_inner();
+			let remaining = expired_requests.split_off(expired);
+			ProofRequests::<T>::put(BoundedVec::truncate_from(remaining));
+			// The deposit pays relayers for the runs spent trying to prove the outcome.
+			let reward_pot = T::RewardPot::get();
+			for request in expired_requests {
+				let repatriated = T::Currency::repatriate_reserved(
+					&request.requester,
+					&reward_pot,
+					request.deposit,
+					BalanceStatus::Free,
+				);
+				if repatriated.is_err() {
+					let _ = T::Currency::slash_reserved(&request.requester, request.deposit);
+				}
+				Self::deposit_event(Event::ProofRequestExpired {
+					who: request.requester,
+					id: request.id.outcome_id(),
+				});
+			}
+			// The requester and the reward pot are written for each expired request.
+			let accounts = 2 * expired as u64;
+			T::DbWeight::get().reads_writes(1 + accounts, 1 + accounts)
+		}
+
+		/// Prune the block producers of the epochs before the last `EpochRetention`, as far as
+		/// `remaining_weight` allows.
+		fn on_idle(_now: T::BlockNumber, remaining_weight: Weight) -> Weight {
+			let db_weight = T::DbWeight::get();
+			let base_weight = db_weight.reads_writes(1, 1);
+			let prune_weight = db_weight.writes(1);
+			if remaining_weight.any_lt(base_weight.saturating_add(prune_weight)) {
+				return Weight::zero()
+			}
+
+			let epochs = StoredEpochs::<T>::get();
+			let excess = epochs.len().saturating_sub(T::EpochRetention::get() as usize);
+			if excess == 0 {
+				return db_weight.reads(1)
+			}
+
+			let budget = remaining_weight.saturating_sub(base_weight).ref_time() /
+				prune_weight.ref_time().max(1);
+			let pruned = excess.min(budget as usize);
+			let mut epochs = epochs.into_inner();
+			for epoch in epochs.drain(..pruned) {
+				BlockProducersByEpoch::<T>::remove(epoch);
+			}
+			StoredEpochs::<T>::put(BoundedVec::truncate_from(epochs));
+			base_weight.saturating_add(prune_weight.saturating_mul(pruned as u64))
+		}
+
+		fn integrity_test() {
+			assert!(
+				(2..MAX_STORED_EPOCHS).contains(&T::EpochRetention::get()),
+				"EpochRetention must keep the head's epoch and the next one"
+			);
+			assert!(!T::RelayerTurn::get().is_zero(), "RelayerTurn must not be zero");
+			assert!(T::MaxHeadersPerBatch::get() > 0, "MaxHeadersPerBatch must not be zero");
+		}
+
 		/// Offchain Worker entry point.
 		///
 		/// By implementing `fn offchain_worker` you declare a new offchain worker.
@@ -137,137 +742,92 @@ pub mod pallet {
 		/// be cases where some blocks are skipped, or for some the worker runs twice (re-orgs),
 		/// so the code should be able to handle that.
 		/// You can use `Local Storage` API to coordinate runs of the worker.
-		fn offchain_worker(_block_number: T::BlockNumber) {
-			let (mut state, bps) = if let Some(head) = LightClientHead::<T>::get() {
-				let state = LightClientState { head, next_bps: None };
-				let bps: Vec<ValidatorStakeView> =
-					BlockProducersByEpoch::<T>::get(state.head.inner_lite.epoch_id)
-						.unwrap()
-						.into_iter()
-						.map(|s| ValidatorStakeView::from(s))
-						.collect();
-				(state, bps)
+		fn offchain_worker(block_number: T::BlockNumber) {
+			if Paused::<T>::get() {
+				log::info!("Light client is paused");
+				return
+			}
+
+			let mut state = if let Some(head) = LightClientHead::<T>::get() {
+				LightClientState { head, next_bps: None }
 			} else {
-				// Bootstrap here
-				log::info!("Bootstrapping light client");
-				let start_block_hash = "BoswxxbPApgouVZNH37jKo6PF9WgrcqqgYjEW8tdXXPU";
+				// The head is only ever advanced by verifying blocks against it, so it has to be
+				// anchored by root first.
+				log::info!(
+					"Light client has no trusted head, set one with `submit_header` and `submit_bps`"
+				);
+				return
+			};
 
-				// get current block and store in `Started`
-				let starting_head = NearRpcClient.fetch_latest_header(start_block_hash);
-				log::info!("Got starting head: {:?}", starting_head.inner_lite.height);
+			// Only the relayer assigned to this turn submits, so relayers don't race each other
+			// with the same blocks and proofs.
+			let relayer = match Self::local_relayer(block_number) {
+				Some(relayer) => relayer,
+				None => {
+					log::debug!("Not this node's turn to relay");
+					return
+				},
+			};
+
+			// Proofs are verified against the head on chain, so they are not submitted together
+			// with a head update that would invalidate them. The head is still synced at least
+			// every other run, so proofs that keep failing on chain don't stall it.
+			if !Self::take_deferred_sync() && Self::process_proof_requests(&relayer, &state.head) {
+				Self::defer_sync();
+				return
+			}
 
-				let bps = starting_head.next_bps.clone().unwrap();
-				let state = LightClientState { head: starting_head.into(), next_bps: None };
-				(state, bps)
+			if Self::backing_off(block_number) {
+				log::debug!("Backing off from the NEAR RPC");
+				return
+			}
+			if Self::submission_in_flight(state.head.inner_lite.height, block_number) {
+				log::debug!("The last submitted block is not included yet");
+				return
+			}
+			// Runs overlap when fetching takes longer than a block, or on re-orgs. The lock
+			// expires, so a worker that died holding it does not stall the others.
+			let mut lock = Self::sync_lock();
+			let mut guard = match lock.try_lock() {
+				Ok(guard) => guard,
+				Err(_) => {
+					log::debug!("Another worker is syncing");
+					return
+				},
 			};
+			log::info!("Syncing from head: {:?}", state.head.inner_lite.height);
 
-			// Here we will have a mechanism to only try to sync if needs be, otherwise we will go
-			// through verification. Receipts to be verified should be stored
-			// reverse-chronologically in a Dequeue, since we will likely already have verified
-			// the earliest headers.
-			// TODO: implement locking on the queue, or at least locking on the syncing aspect
-			// TODO: then another worker can start verifying TXs
-			let verification_queue: Vec<BlockHeight> = Vec::new();
-
-			// determine if should sync by checking if last tx in the queue is newer than our
-			// head
-
-			// blockbend note
-			// U2FsdGVkX1+ZBNtoxCa2YwdHh5ibQZp9rxbVeSbQmiHLE1xRzahvPEazun/
-			// dBGIl6p2vTiP83xDw4iWHMfcth29WDXNHBRx1UiOssU1aSPumMd4ZlILOgSfxl9gZlfTrzMLy9yN2gqk58OpADl+RwVyc2TQC+NDbOgDZxQgsqFimSCyj72p5EqcOdfuIOFks!
-			// e569e0f4f2a1239663f8161979251972ec246dae
-
-			// U2FsdGVkX182uv/
-			// cRqnqcIi+4Ms9ez3CIzGMbYyHJb7xSL2Wwl0zrLt0t7ZqIFGEYXp3PkCZ5VT+mgxVyTQyrCM2Nt9aFaiDMk6OMlFMs1nlH754TwMGb4yHGW7T53nmIlXTJC3SuJEoRl9AQwDJ/
-			// 8ImX5fTBiJ61/Njt6TK6ARiBnzyulL9G2ncWZR0idvv!070f63246269818372f679634f76275e1fc53b23
-			let last_is_newer = verification_queue
-				.last()
-				.map(|h| h > &state.head.inner_lite.height)
-				.unwrap_or(false);
-
-			let should_sync = last_is_newer || verification_queue.is_empty();
-			log::info!("Should sync: {:?}", should_sync);
-
-			if should_sync {
-				log::info!("Syncing from head: {:?}", state.head.inner_lite.height);
-
-				// TODO: if so start verifying from queue
-				let new_head = NearRpcClient.fetch_latest_header(&format!("{}", state.head.hash()));
-
-				if state.validate_and_update_head(&new_head, bps) {
-					if let Err(e) = Self::try_submit(Some(state.head), state.next_bps) {
-						log::error!("Failed to submit {:?}", e);
-					}
-				}
-			} else {
-				// TODO: start verifying from front of queue
-				// TODO: also verify some from middle to avoid ddos
+			let max_blocks = match Self::relayer_mode() {
+				RelayerMode::Unsigned => T::MaxHeadersPerBatch::get(),
+				RelayerMode::Signed => 1,
+			};
+			let mut blocks =
+				Self::fetch_verified_blocks(block_number, &mut state, max_blocks, &mut guard);
+			let height = state.head.inner_lite.height;
+			let result = match blocks.len() {
+				0 => return,
+				1 => Self::try_submit(relayer, blocks.remove(0)),
+				_ => Self::try_submit_headers(relayer, blocks),
+			};
+			match result {
+				Ok(()) => Self::mark_submitted(height, block_number),
+				Err(e) => log::error!("Failed to submit {:?}", e),
 			}
 		}
 	}
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
-		/// Check if we have fetched the data before. If yes, we can use the cached version
-		///   stored in off-chain worker storage `storage`. If not, we fetch the remote info and
-		///   write the info into the storage for future retrieval.
-		// fn fetch_remote_info() -> Result<(), Error<T>> {
-		// // Create a reference to Local Storage value.
-		// // Since the local storage is common for all offchain workers, it's a good practice
-		// // to prepend our entry with the pallet name.
-		// let s_info = StorageValueRef::persistent(b"near::hn-info");
-
-		// // Local storage is persisted and shared between runs of the offchain workers,
-		// // offchain workers may run concurrently. We can use the `mutate` function to
-		// // write a storage entry in an atomic fashion.
-		// //
-		// // With a similar API as `StorageValue` with the variables `get`, `set`, `mutate`.
-		// // We will likely want to use `mutate` to access
-		// // the storage comprehensively.
-		// //
-		// if let Ok(Some(info)) = s_info.get::<HackerNewsInfo>() {
-		// 	// hn-info has already been fetched. Return early.
-		// 	log::info!("cached hn-info: {:?}", info);
-		// 	return Ok(())
-		// }
-
-		// // Since off-chain storage can be accessed by off-chain workers from multiple runs,
-		// it // is important to lock   it before doing heavy computations or write operations.
-		// //
-		// // There are four ways of defining a lock:
-		// //   1) `new` - lock with default time and block exipration
-		// //   2) `with_deadline` - lock with default block but custom time expiration
-		// //   3) `with_block_deadline` - lock with default time but custom block expiration
-		// //   4) `with_block_and_time_deadline` - lock with custom time and block expiration
-		// // Here we choose the most custom one for demonstration purpose.
-		// let mut lock = StorageLock::<BlockAndTime<Self>>::with_block_and_time_deadline(
-		// 	b"near::lock",
-		// 	LOCK_BLOCK_EXPIRATION,
-		// 	Duration::from_millis(LOCK_TIMEOUT_EXPIRATION),
-		// );
-
-		// // We try to acquire the lock here. If failed, we know the `fetch_n_parse` part
-		// inside // is being   executed by previous run of ocw, so the function just returns.
-		// if let Ok(_guard) = lock.try_lock() {
-		// 	match Self::fetch_n_parse() {
-		// 		Ok(info) => {
-		// 			s_info.set(&info);
-		// 		},
-		// 		Err(err) => return Err(err),
-		// 	}
-		// }
-		// Ok(())
-		// }
 		#[pallet::weight(0)]
 		#[pallet::call_index(0)]
 		pub fn submit_header(
 			origin: OriginFor<T>,
 			head: LightClientBlockLiteView,
 		) -> DispatchResult {
-			let _who = ensure_root(origin)?;
+			T::AdminOrigin::ensure_origin(origin)?;
 
 			log::info!("Storing new head: {:?}", head);
-			LightClientHead::<T>::put(head);
+			Self::set_head(head);
 
 			Ok(())
 		}
@@ -277,72 +837,1001 @@ pub mod pallet {
 		pub fn submit_bps(
 			origin: OriginFor<T>,
 			epoch: CryptoHash,
-			next_bps: Vec<ValidatorStakeViewScaleHax>,
+			next_bps: Vec<ValidatorStakeView>,
 		) -> DispatchResult {
-			let _who = ensure_root(origin)?;
+			T::AdminOrigin::ensure_origin(origin)?;
 
 			log::info!("Storing bps: {:?}", next_bps.len());
-			let next_bps: BoundedVec<ValidatorStakeViewScaleHax, ConstU32<MAX_BLOCK_PRODUCERS>> =
-				BoundedVec::try_from(next_bps).unwrap();
-			BlockProducersByEpoch::<T>::insert(epoch, next_bps);
+			let next_bps: BoundedVec<ValidatorStakeView, ConstU32<MAX_BLOCK_PRODUCERS>> =
+				BoundedVec::try_from(next_bps).map_err(|_| Error::<T>::TooManyBlockProducers)?;
+			Self::store_block_producers(epoch, next_bps);
 
 			Ok(())
 		}
-		#[pallet::weight(1_000_000)]
+
+		/// Submit the next light client block.
+		///
+		/// The block is verified against the stored head and block producers, and only stored if
+		/// it passes. A block that fails verification is reported with `HeaderRejected` rather
+		/// than failing the call, so the reason is not rolled back with it.
+		#[pallet::weight(Pallet::<T>::block_view_weight(&payload.block_view))]
 		#[pallet::call_index(2)]
 		pub fn submit(
 			origin: OriginFor<T>,
-			head: Option<LightClientBlockLiteView>,
-			bps: Option<(CryptoHash, Vec<ValidatorStakeViewScaleHax>)>,
+			payload: BlockViewPayload<T::Public>,
+			_signature: T::Signature,
 		) -> DispatchResult {
-			if let Some(head) = head {
-				log::info!("Received request to submit head {}", head.inner_lite.height);
-				LightClientHead::<T>::put(head);
+			// The signature is checked in `validate_unsigned`.
+			ensure_none(origin)?;
+			Self::ensure_relayer_turn(&payload.public.into_account())?;
+			Self::advance_head(payload.block_view)?;
+			Ok(())
+		}
 
-				// Self::submit_header(origin.clone(), head)?
-			}
-			if let Some((epoch, next_bps)) = bps {
-				log::info!(
-					"Received request to submit bps of len {} for epoch {:?}",
-					next_bps.len(),
-					epoch
+		/// Verify that a NEAR transaction or receipt outcome is part of the chain known to the
+		/// light client.
+		///
+		/// The proof is the response of the `EXPERIMENTAL_light_client_proof` RPC, requested with
+		/// the current light client head.
+		#[pallet::weight(Pallet::<T>::execution_proof_weight(proof))]
+		#[pallet::call_index(3)]
+		pub fn verify_execution_proof(
+			origin: OriginFor<T>,
+			proof: RpcLightClientExecutionProofResponse,
+		) -> DispatchResult {
+			ensure_signed(origin)?;
+			Self::do_verify_execution_proof(proof)
+		}
+
+		/// Submit an execution outcome proof fetched by the offchain worker.
+		#[pallet::weight(
+			Pallet::<T>::execution_proof_weight(&payload.proof)
+				.saturating_add(T::DbWeight::get().reads(1))
+		)]
+		#[pallet::call_index(4)]
+		pub fn submit_execution_proof(
+			origin: OriginFor<T>,
+			payload: ExecutionProofPayload<T::Public>,
+			_signature: T::Signature,
+		) -> DispatchResult {
+			// The signature is checked in `validate_unsigned`.
+			ensure_none(origin)?;
+			Self::ensure_relayer_turn(&payload.public.into_account())?;
+			Self::do_verify_execution_proof(payload.proof)
+		}
+
+		/// Ask the offchain workers to prove the outcome of a NEAR transaction or receipt for
+		/// `target`, or for the caller if it is `None`.
+		///
+		/// A `deposit` of at least `ProofRequestDeposit` is reserved, if any, until the outcome
+		/// is verified. `ProofRequestFulfilled` is then emitted and `OnOutcomeVerified` called
+		/// for `target`. If the request expires first, the deposit goes to `RewardPot`.
+		#[pallet::weight(
+			T::DbWeight::get().reads_writes(2, 2).saturating_add(Weight::from_parts(
+				PROOF_REQUEST_WEIGHT.saturating_mul(u64::from(T::MaxProofRequests::get())),
+				0,
+			))
+		)]
+		#[pallet::call_index(5)]
+		pub fn request_proof(
+			origin: OriginFor<T>,
+			id: TransactionOrReceiptId,
+			deposit: Option<BalanceOf<T>>,
+			target: Option<T::AccountId>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let outcome_id = id.outcome_id();
+			ensure!(
+				!VerifiedOutcomes::<T>::contains_key(outcome_id),
+				Error::<T>::OutcomeAlreadyVerified
+			);
+			let deposit = match deposit {
+				Some(deposit) => {
+					ensure!(deposit >= T::ProofRequestDeposit::get(), Error::<T>::DepositTooLow);
+					deposit
+				},
+				None => Zero::zero(),
+			};
+			let target = target.unwrap_or_else(|| who.clone());
+
+			ProofRequests::<T>::try_mutate(|requests| {
+				ensure!(
+					!requests.iter().any(|request| request.id.outcome_id() == outcome_id),
+					Error::<T>::OutcomeAlreadyRequested
 				);
-				let next_bps: BoundedVec<
-					ValidatorStakeViewScaleHax,
-					ConstU32<MAX_BLOCK_PRODUCERS>,
-				> = BoundedVec::try_from(next_bps).unwrap();
-				BlockProducersByEpoch::<T>::insert(epoch, next_bps);
-				// Self::submit_bps(origin, epoch, next_bps)?
+				requests
+					.try_push(ProofRequest {
+						requester: who.clone(),
+						target: target.clone(),
+						id,
+						deposit,
+						requested_at: frame_system::Pallet::<T>::block_number(),
+					})
+					.map_err(|_| Error::<T>::TooManyProofRequests)?;
+				T::Currency::reserve(&who, deposit)
+			})?;
+
+			Self::deposit_event(Event::ProofRequested { who, id: outcome_id, target, deposit });
+			Ok(())
+		}
+
+		/// Reset the trusted head and the block producers of `epoch_id`, to recover a light
+		/// client that can no longer follow the chain, e.g. after being offline for more than an
+		/// epoch.
+		///
+		/// `epoch_id` must be the head's next epoch, whose block producers must match its
+		/// `next_bp_hash`. Like the genesis head, the head must be the last block of its epoch.
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 2))]
+		#[pallet::call_index(6)]
+		pub fn reanchor(
+			origin: OriginFor<T>,
+			head: LightClientBlockLiteView,
+			epoch_id: CryptoHash,
+			block_producers: Vec<ValidatorStakeView>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			ensure!(
+				epoch_id == head.inner_lite.next_epoch_id,
+				Error::<T>::UnverifiableBlockProducers
+			);
+			ensure!(
+				Self::matches_next_bp_hash(&head, &block_producers),
+				Error::<T>::InvalidNextBlockProducersHash
+			);
+			let block_producers: BoundedVec<_, ConstU32<MAX_BLOCK_PRODUCERS>> =
+				BoundedVec::try_from(block_producers)
+					.map_err(|_| Error::<T>::TooManyBlockProducers)?;
+
+			let previous = LightClientHead::<T>::get().map(|head| head.hash());
+			let (height, hash) = (head.inner_lite.height, head.hash());
+			Self::store_block_producers(epoch_id, block_producers);
+			Self::set_head(head);
+
+			Self::deposit_event(Event::Reanchored { previous, height, hash });
+			Ok(())
+		}
+
+		/// Stop accepting blocks and execution proofs.
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		#[pallet::call_index(7)]
+		pub fn pause(origin: OriginFor<T>) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			Paused::<T>::put(true);
+			Self::deposit_event(Event::LightClientPaused);
+			Ok(())
+		}
+
+		/// Accept blocks and execution proofs again.
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		#[pallet::call_index(8)]
+		pub fn resume(origin: OriginFor<T>) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			Paused::<T>::kill();
+			Self::deposit_event(Event::LightClientResumed);
+			Ok(())
+		}
+
+		/// Relay the next light client block from a signed account.
+		///
+		/// The block is verified like with `submit`. If it advances the head, the fee is refunded
+		/// and the relayer is paid `HeaderReward` from `RewardPot`, plus `EpochReward` if the
+		/// block is the first of an epoch. Otherwise the relayer pays the fee.
+		#[pallet::weight(
+			Pallet::<T>::block_view_weight(block_view)
+				.saturating_add(T::DbWeight::get().reads_writes(2, 2))
+		)]
+		#[pallet::call_index(9)]
+		pub fn relay(
+			origin: OriginFor<T>,
+			block_view: LightClientBlockView,
+		) -> DispatchResultWithPostInfo {
+			let relayer = ensure_signed(origin)?;
+			Self::ensure_relayer_turn(&relayer)?;
+
+			let epoch_id = block_view.inner_lite.epoch_id;
+			let previous_epoch_id =
+				LightClientHead::<T>::get().map(|head| head.inner_lite.epoch_id);
+			if Self::advance_head(block_view)?.is_none() {
+				return Ok(Pays::Yes.into())
+			}
+
+			Self::reward_relayer(&relayer, previous_epoch_id != Some(epoch_id));
+			Ok(Pays::No.into())
+		}
+
+		/// Submit a chain of light client blocks, to catch up with NEAR.
+		///
+		/// The blocks are verified and stored in order like with `submit`, stopping at the first
+		/// block that is rejected since the blocks after it build on it.
+		#[pallet::weight(
+			payload.block_views.iter().fold(Weight::zero(), |weight, block_view| {
+				weight.saturating_add(Pallet::<T>::block_view_weight(block_view))
+			})
+		)]
+		#[pallet::call_index(12)]
+		pub fn submit_headers(
+			origin: OriginFor<T>,
+			payload: HeadersPayload<T::Public>,
+			_signature: T::Signature,
+		) -> DispatchResult {
+			// The signature is checked in `validate_unsigned`.
+			ensure_none(origin)?;
+			Self::ensure_relayer_turn(&payload.public.into_account())?;
+			ensure!(
+				payload.block_views.len() <= T::MaxHeadersPerBatch::get() as usize,
+				Error::<T>::TooManyHeaders
+			);
+
+			for block_view in payload.block_views {
+				if Self::advance_head(block_view)?.is_none() {
+					break
+				}
+			}
+			Ok(())
+		}
+
+		/// Allow `who` to submit light client blocks, taking turns after the other relayers.
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		#[pallet::call_index(10)]
+		pub fn register_relayer(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			Relayers::<T>::try_mutate(|relayers| {
+				ensure!(!relayers.contains(&who), Error::<T>::AlreadyRelayer);
+				relayers.try_push(who.clone()).map_err(|_| Error::<T>::TooManyRelayers)
+			})?;
+			Self::deposit_event(Event::RelayerRegistered { who });
+			Ok(())
+		}
+
+		/// Stop `who` from submitting light client blocks.
+		///
+		/// Called by the admin origin, or by the relayer itself.
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		#[pallet::call_index(11)]
+		pub fn deregister_relayer(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+			if let Err(origin) = T::AdminOrigin::try_origin(origin) {
+				ensure!(ensure_signed(origin)? == who, DispatchError::BadOrigin);
 			}
+
+			Relayers::<T>::try_mutate(|relayers| {
+				let index =
+					relayers.iter().position(|r| *r == who).ok_or(Error::<T>::NotRelayer)?;
+				relayers.remove(index);
+				Ok::<_, Error<T>>(())
+			})?;
+			Self::deposit_event(Event::RelayerDeregistered { who });
 			Ok(())
 		}
 	}
 
+	impl<T: Config> NearOutcomeProvider<T::BlockNumber> for Pallet<T> {
+		fn verified_outcome(id: &CryptoHash) -> Option<VerifiedOutcome<T::BlockNumber>> {
+			VerifiedOutcomes::<T>::get(id)
+		}
+	}
+
 	impl<T: Config> Pallet<T> {
-		pub fn try_submit(
-			head: Option<LightClientBlockLiteView>,
-			bps: Option<(CryptoHash, Vec<ValidatorStakeViewScaleHax>)>,
+		/// Verify `block_view` against the head and make it the new head if it passes.
+		///
+		/// A block that fails verification is reported with `HeaderRejected` and `None` is
+		/// returned, rather than an error that would roll the event back.
+		fn advance_head(
+			block_view: LightClientBlockView,
+		) -> Result<Option<HeadUpdate>, DispatchError> {
+			ensure!(!Paused::<T>::get(), Error::<T>::Paused);
+
+			let height = block_view.inner_lite.height;
+			log::info!("Received request to submit head {}", height);
+
+			let head = LightClientHead::<T>::get().ok_or(Error::<T>::NoTrustedHead)?;
+			let mut state = LightClientState { head, next_bps: None };
+			let result = match state.signing_epoch(&block_view) {
+				Ok(epoch_id) => {
+					let bps = Self::epoch_block_producers(&epoch_id)
+						.ok_or(Error::<T>::UnknownBlockProducers)?;
+					state.validate_and_update_head(&block_view, bps)
+				},
+				Err(reason) => Err(reason),
+			};
+			match result {
+				Ok(update) => {
+					if let Some((epoch, next_bps)) = state.next_bps {
+						log::info!("Storing bps of len {} for epoch {:?}", next_bps.len(), epoch);
+						let next_bps: BoundedVec<_, ConstU32<MAX_BLOCK_PRODUCERS>> =
+							BoundedVec::try_from(next_bps)
+								.map_err(|_| Error::<T>::TooManyBlockProducers)?;
+						Self::store_block_producers(epoch, next_bps);
+					}
+					Self::set_head(state.head);
+					Self::deposit_event(Event::HeadUpdated {
+						height: update.height,
+						hash: update.hash,
+					});
+					Ok(Some(update))
+				},
+				Err(reason) => {
+					log::warn!("Rejected block {}: {}", height, reason);
+					Self::deposit_event(Event::HeaderRejected { height, reason });
+					Ok(None)
+				},
+			}
+		}
+
+		/// The weight of verifying `block_view` and making it the head.
+		///
+		/// Every approval may have to be verified against a block producer of the signing epoch,
+		/// and the next block producers, if any, are hashed and stored.
+		pub fn block_view_weight(block_view: &LightClientBlockView) -> Weight {
+			let approvals = block_view.approvals_after_next.len() as u64;
+			let signatures =
+				block_view.approvals_after_next.iter().filter(|a| a.is_some()).count() as u64;
+			let next_bps = block_view.next_bps.as_ref().map_or(0, Vec::len) as u64;
+
+			let db_weight = T::DbWeight::get();
+			// The relayers, pause flag, head, signing epoch and recent heads are read, and the
+			// head and recent heads written.
+			let mut weight = db_weight
+				.reads_writes(5, 3)
+				.saturating_add(Weight::from_parts(APPROVAL_WEIGHT.saturating_mul(signatures), 0))
+				.saturating_add(Weight::from_parts(
+					BLOCK_PRODUCER_WEIGHT.saturating_mul(approvals.saturating_add(next_bps)),
+					0,
+				));
+			if next_bps > 0 {
+				weight = weight.saturating_add(db_weight.reads_writes(1, 3));
+			}
+			weight
+		}
+
+		/// The weight of verifying `proof` and recording its outcome.
+		///
+		/// Every log, receipt id and returned value of the outcome is hashed, and so is every
+		/// node of the outcome, outcome root and block merkle paths.
+		pub fn execution_proof_weight(proof: &RpcLightClientExecutionProofResponse) -> Weight {
+			let outcome = &proof.outcome_proof.outcome;
+			let value = match &outcome.status {
+				ExecutionStatusView::SuccessValue(value) => value.len(),
+				_ => 0,
+			};
+			let bytes = outcome
+				.logs
+				.iter()
+				.map(|log| log.len())
+				.sum::<usize>()
+				.saturating_add(outcome.receipt_ids.len().saturating_mul(32))
+				.saturating_add(value) as u64;
+			let nodes = proof
+				.outcome_proof
+				.proof
+				.len()
+				.saturating_add(proof.outcome_root_proof.len())
+				.saturating_add(proof.block_proof.len())
+				.saturating_add(outcome.logs.len()) as u64;
+
+			// The pause flag, head, verified outcomes and proof requests are read, and the
+			// verified outcome, proof requests and the requester's reserve written. The request
+			// for the outcome is looked up in the whole queue.
+			let requests = u64::from(T::MaxProofRequests::get());
+			T::DbWeight::get()
+				.reads_writes(4, 3)
+				.saturating_add(Weight::from_parts(MERKLE_NODE_WEIGHT.saturating_mul(nodes), 0))
+				.saturating_add(Weight::from_parts(OUTCOME_BYTE_WEIGHT.saturating_mul(bytes), 0))
+				.saturating_add(Weight::from_parts(
+					PROOF_REQUEST_WEIGHT.saturating_mul(requests),
+					0,
+				))
+		}
+
+		/// The relayer whose turn it is to submit light client blocks at block `now`.
+		pub fn assigned_relayer(now: T::BlockNumber) -> Option<T::AccountId> {
+			let relayers = Relayers::<T>::get();
+			if relayers.is_empty() {
+				return None
+			}
+			let turn: u64 = (now / T::RelayerTurn::get()).unique_saturated_into();
+			relayers.get((turn % relayers.len() as u64) as usize).cloned()
+		}
+
+		/// Pool validity of a submission signed by `public`, as far as relayers go.
+		fn validate_relayer(public: &T::Public) -> Result<(), TransactionValidityError> {
+			match Self::ensure_relayer_turn(&public.clone().into_account()) {
+				Ok(()) => Ok(()),
+				Err(Error::<T>::NotRelayerTurn) =>
+					Err(InvalidTransaction::Custom(NOT_RELAYER_TURN).into()),
+				Err(_) => Err(InvalidTransaction::BadSigner.into()),
+			}
+		}
+
+		/// Ensure `who` is the relayer assigned to the current block.
+		///
+		/// Submissions are built by offchain workers for the next block, so the relayer of the
+		/// previous block is still accepted.
+		fn ensure_relayer_turn(who: &T::AccountId) -> Result<(), Error<T>> {
+			ensure!(Relayers::<T>::get().contains(who), Error::<T>::NotRelayer);
+
+			let now = frame_system::Pallet::<T>::block_number();
+			let assigned = [now, now.saturating_sub(One::one())]
+				.into_iter()
+				.any(|block| Self::assigned_relayer(block).as_ref() == Some(who));
+			ensure!(assigned, Error::<T>::NotRelayerTurn);
+			Ok(())
+		}
+
+		/// The local key of the relayer assigned to block `now`, if this node has it.
+		fn local_relayer(now: T::BlockNumber) -> Option<T::Public> {
+			type Crypto<T> = <T as Config>::AuthorityId;
+			type Public<T> = <T as SigningTypes>::Public;
+			type Signature<T> = <T as SigningTypes>::Signature;
+
+			let assigned = Self::assigned_relayer(now)?;
+			<Crypto<T> as AppCrypto<Public<T>, Signature<T>>>::RuntimeAppPublic::all()
+				.into_iter()
+				.map(|key| {
+					let generic: <Crypto<T> as AppCrypto<Public<T>, Signature<T>>>::GenericPublic =
+						key.into();
+					generic.into()
+				})
+				.find(|public: &T::Public| public.clone().into_account() == assigned)
+		}
+
+		/// Pay `relayer` for advancing the head, if `RewardPot` can afford it.
+		fn reward_relayer(relayer: &T::AccountId, new_epoch: bool) {
+			let mut amount = T::HeaderReward::get();
+			if new_epoch {
+				amount = amount.saturating_add(T::EpochReward::get());
+			}
+			if amount.is_zero() {
+				return
+			}
+
+			let pot = T::RewardPot::get();
+			match T::Currency::transfer(&pot, relayer, amount, ExistenceRequirement::KeepAlive) {
+				Ok(()) =>
+					Self::deposit_event(Event::RelayerRewarded { who: relayer.clone(), amount }),
+				Err(e) => log::warn!("Failed to reward relayer: {:?}", e),
+			}
+		}
+
+		/// Verify an execution outcome proof against the trusted head, without storing it.
+		pub fn check_execution_proof(
+			proof: &RpcLightClientExecutionProofResponse,
+		) -> Result<VerifiedOutcome<T::BlockNumber>, ExecutionProofError> {
+			ensure!(!Paused::<T>::get(), ExecutionProofError::Paused);
+			let id = proof.outcome_proof.id;
+			ensure!(!VerifiedOutcomes::<T>::contains_key(id), ExecutionProofError::AlreadyVerified);
+
+			let head = LightClientHead::<T>::get().ok_or(ExecutionProofError::NoTrustedHead)?;
+			verify_execution_proof(&head, proof).map_err(|e| {
+				log::warn!("Rejected execution proof of {}: {}", id, e);
+				ExecutionProofError::InvalidProof(e)
+			})?;
+
+			Ok(VerifiedOutcome {
+				executor_id: proof.outcome_proof.outcome.executor_id.clone(),
+				status: (&proof.outcome_proof.outcome.status).into(),
+				outcome_hash: CryptoHash::hash_borsh(proof.outcome_proof.to_hashes()),
+				block_hash: proof.block_header_lite.hash(),
+				verified_at: frame_system::Pallet::<T>::block_number(),
+			})
+		}
+
+		fn do_verify_execution_proof(
+			proof: RpcLightClientExecutionProofResponse,
 		) -> DispatchResult {
+			let outcome = Self::check_execution_proof(&proof).map_err(Error::<T>::from)?;
+			let id = proof.outcome_proof.id;
+			VerifiedOutcomes::<T>::insert(id, &outcome);
+			Self::fulfill_proof_request(&id, &outcome);
+
+			Self::deposit_event(Event::ExecutionOutcomeVerified {
+				id,
+				block_hash: proof.block_header_lite.hash(),
+				outcome: proof.outcome_proof.outcome,
+			});
+			Ok(())
+		}
+
+		/// Remove the request for the outcome `id`, if any, refunding its deposit.
+		fn fulfill_proof_request(id: &CryptoHash, outcome: &VerifiedOutcome<T::BlockNumber>) {
+			let request = ProofRequests::<T>::mutate(|requests| {
+				let index = requests.iter().position(|request| request.id.outcome_id() == *id)?;
+				Some(requests.remove(index))
+			});
+			if let Some(request) = request {
+				T::Currency::unreserve(&request.requester, request.deposit);
+				T::OnOutcomeVerified::on_outcome_verified(
+					&request.requester,
+					&request.target,
+					id,
+					outcome,
+				);
+				Self::deposit_event(Event::ProofRequestFulfilled {
+					who: request.requester,
+					id: *id,
+					target: request.target,
+				});
+			}
+		}
+
+		/// Fetch and submit the proofs of the oldest `ProofRequestsPerBlock` requests made on
+		/// chain, and of the requests in offchain local storage, against `head`.
+		///
+		/// Returns whether any proof was submitted.
+		fn process_proof_requests(relayer: &T::Public, head: &LightClientBlockLiteView) -> bool {
+			let client = Self::rpc_client();
+			let head_hash = format!("{}", head.hash());
+			let mut submitted = false;
+
+			// Requests made on chain stay queued until they are fulfilled or expire.
+			let requests = ProofRequests::<T>::get().into_inner();
+			for request in Self::next_proof_requests(requests) {
+				let outcome_id = request.id.outcome_id();
+				match client.fetch_execution_proof(request.id.into(), &head_hash) {
+					Ok(proof) => match Self::try_submit_proof(relayer.clone(), proof) {
+						Ok(()) => submitted = true,
+						Err(e) => log::error!("Failed to submit proof {:?}", e),
+					},
+					Err(e) => log::warn!("Failed to fetch proof of {}: {}", outcome_id, e),
+				}
+			}
+
+			Self::process_local_proof_requests(relayer, &client, &head_hash) || submitted
+		}
+
+		/// The next `ProofRequestsPerBlock` of `requests` to process. Runs take turns through the
+		/// requests, so requests that can't be proven yet don't hold up the ones behind them.
+		pub(crate) fn next_proof_requests(
+			requests: Vec<ProofRequestOf<T>>,
+		) -> Vec<ProofRequestOf<T>> {
+			if requests.is_empty() {
+				return requests
+			}
+			let storage = StorageValueRef::persistent(PROOF_REQUEST_CURSOR_KEY);
+			let len = requests.len();
+			let cursor = storage.get::<u32>().ok().flatten().unwrap_or(0) as usize % len;
+			let count = (T::ProofRequestsPerBlock::get() as usize).min(len);
+			storage.set(&(((cursor + count) % len) as u32));
+			requests.into_iter().cycle().skip(cursor).take(count).collect()
+		}
+
+		/// Remember to sync the head in the next run, rather than submit proofs.
+		pub(crate) fn defer_sync() {
+			StorageValueRef::persistent(SYNC_DEFERRED_KEY).set(&true);
+		}
+
+		/// Whether the last run deferred syncing the head to this one, clearing it.
+		pub(crate) fn take_deferred_sync() -> bool {
+			let storage = StorageValueRef::persistent(SYNC_DEFERRED_KEY);
+			let deferred = storage.get::<bool>().ok().flatten().unwrap_or(false);
+			storage.clear();
+			deferred
+		}
+
+		/// Fetch and submit the proofs requested in offchain local storage. Requests that fail
+		/// for reasons that may pass are kept for the next run.
+		///
+		/// Returns whether any proof was submitted.
+		fn process_local_proof_requests(
+			relayer: &T::Public,
+			client: &NearRpcClient,
+			head_hash: &str,
+		) -> bool {
+			let requests = match frame_support::sp_io::offchain::local_storage_get(
+				StorageKind::PERSISTENT,
+				PROOF_REQUESTS_KEY,
+			) {
+				Some(requests) => requests,
+				None => return false,
+			};
+			let requests: Vec<LightClientProofParams> = match serde_json::from_slice(&requests) {
+				Ok(requests) => requests,
+				Err(e) => {
+					log::warn!("Invalid proof requests: {}", e);
+					return false
+				},
+			};
+			if requests.is_empty() {
+				return false
+			}
+
+			let mut submitted = false;
+			let mut remaining = Vec::new();
+			for params in requests {
+				match client.fetch_execution_proof(params.clone(), head_hash) {
+					Ok(proof) => match Self::try_submit_proof(relayer.clone(), proof) {
+						Ok(()) => submitted = true,
+						Err(e) => {
+							log::error!("Failed to submit proof {:?}", e);
+							remaining.push(params);
+						},
+					},
+					Err(e)
+						if e.is_endpoint_failure() ||
+							e.cause() == Some(&NearErrorCause::NotConfirmed) =>
+					{
+						log::warn!("Failed to fetch proof of {:?}, retrying later: {}", params, e);
+						remaining.push(params);
+					},
+					Err(e) => log::warn!("Dropping proof request {:?}: {}", params, e),
+				}
+			}
+
+			if let Ok(remaining) = serde_json::to_vec(&remaining) {
+				frame_support::sp_io::offchain::local_storage_set(
+					StorageKind::PERSISTENT,
+					PROOF_REQUESTS_KEY,
+					&remaining,
+				);
+			}
+			submitted
+		}
+
+		/// Fetch the block after `head_hash`, going to the archival endpoints if the RPC endpoints
+		/// no longer have the head.
+		fn fetch_next_block(
+			block_number: T::BlockNumber,
+			head_hash: CryptoHash,
+		) -> Option<LightClientBlockView> {
+			let client = Self::rpc_client();
+			let head_hash = format!("{}", head_hash);
+			let result = match client.fetch_latest_header(&head_hash) {
+				Err(e)
+					if matches!(
+						e.cause(),
+						Some(NearErrorCause::UnknownBlock | NearErrorCause::GarbageCollectedBlock)
+					) =>
+				{
+					log::info!("Head is not on the RPC endpoints ({}), trying the archive", e);
+					client.fetch_archived_header(&head_hash)
+				},
+				result => result,
+			};
+
+			match result {
+				Ok(new_head) => {
+					Self::reset_backoff();
+					if new_head.is_none() {
+						log::debug!("No block newer than the head yet");
+					}
+					new_head
+				},
+				Err(e) if e.is_timeout() => {
+					let until = Self::back_off(block_number);
+					log::warn!("NEAR RPC timed out: {}, backing off until block {:?}", e, until);
+					None
+				},
+				Err(e) => {
+					log::warn!("Failed to fetch the next block: {}", e);
+					None
+				},
+			}
+		}
+
+		/// Fetch and verify up to `max_blocks` blocks following the head of `state`, advancing it.
+		///
+		/// Once a block is in the head's epoch the worker has caught up with NEAR, which only
+		/// serves blocks an epoch at a time. Until then it keeps going for as long as it holds
+		/// the sync lock.
+		fn fetch_verified_blocks(
+			block_number: T::BlockNumber,
+			state: &mut LightClientState,
+			max_blocks: u32,
+			guard: &mut StorageLockGuard<'_, '_, BlockAndTime<frame_system::Pallet<T>>>,
+		) -> Vec<LightClientBlockView> {
+			let mut blocks = Vec::new();
+			while blocks.len() < max_blocks as usize {
+				let new_head = match Self::fetch_next_block(block_number, state.head.hash()) {
+					Some(new_head) => new_head,
+					None => break,
+				};
+				let height = new_head.inner_lite.height;
+
+				// The block is signed by the producers of its own epoch, which is the head's
+				// next epoch when it crosses an epoch boundary. Those were only learned locally
+				// if the head crossed the previous boundary in this run.
+				let bps = match state.signing_epoch(&new_head) {
+					Ok(epoch_id) => match &state.next_bps {
+						Some((epoch, bps)) if *epoch == epoch_id => bps.clone(),
+						_ => match Self::epoch_block_producers(&epoch_id) {
+							Some(bps) => bps,
+							None => {
+								log::warn!("No block producers for epoch {}", epoch_id);
+								break
+							},
+						},
+					},
+					Err(e) => {
+						log::warn!("Rejected block {}: {}", height, e);
+						break
+					},
+				};
+
+				let epoch_id = state.head.inner_lite.epoch_id;
+				match state.validate_and_update_head(&new_head, bps) {
+					Ok(update) => log::info!("Verified head {} ({})", update.height, update.hash),
+					Err(e) => {
+						log::warn!("Rejected block {}: {}", height, e);
+						break
+					},
+				}
+				let caught_up = new_head.inner_lite.epoch_id == epoch_id;
+				blocks.push(new_head);
+				if caught_up || guard.extend_lock().is_err() {
+					break
+				}
+			}
+			blocks
+		}
+
+		/// Whether the worker is backing off from the NEAR RPC at `now`.
+		pub(crate) fn backing_off(now: T::BlockNumber) -> bool {
+			StorageValueRef::persistent(RPC_BACKOFF_KEY)
+				.get::<(T::BlockNumber, u32)>()
+				.ok()
+				.flatten()
+				.map_or(false, |(until, _)| now < until)
+		}
+
+		/// Back off from the NEAR RPC, for twice as long as the last time. Returns the block
+		/// until which the worker backs off.
+		pub(crate) fn back_off(now: T::BlockNumber) -> T::BlockNumber {
+			let storage = StorageValueRef::persistent(RPC_BACKOFF_KEY);
+			let timeouts = storage
+				.get::<(T::BlockNumber, u32)>()
+				.ok()
+				.flatten()
+				.map_or(0, |(_, timeouts)| timeouts.saturating_add(1));
+			let until = now.saturating_add((1u32 << timeouts.min(MAX_BACKOFF_EXPONENT)).into());
+			storage.set(&(until, timeouts));
+			until
+		}
+
+		pub(crate) fn reset_backoff() {
+			StorageValueRef::persistent(RPC_BACKOFF_KEY).clear();
+		}
+
+		/// The lock a worker holds while it fetches, verifies and submits the next block.
+		pub(crate) fn sync_lock() -> StorageLock<'static, BlockAndTime<frame_system::Pallet<T>>> {
+			StorageLock::with_block_and_time_deadline(
+				SYNC_LOCK_KEY,
+				LOCK_BLOCK_EXPIRATION,
+				Duration::from_millis(LOCK_TIMEOUT_EXPIRATION),
+			)
+		}
+
+		/// Remember that the block at `height` was submitted at block `now`.
+		pub(crate) fn mark_submitted(height: BlockHeight, now: T::BlockNumber) {
+			StorageValueRef::persistent(LAST_SUBMITTED_KEY).set(&(height, now));
+		}
+
+		/// Whether a block newer than the head at `head_height` was submitted recently enough
+		/// to still be in the transaction pool at `now`.
+		pub(crate) fn submission_in_flight(head_height: BlockHeight, now: T::BlockNumber) -> bool {
+			StorageValueRef::persistent(LAST_SUBMITTED_KEY)
+				.get::<(BlockHeight, T::BlockNumber)>()
+				.ok()
+				.flatten()
+				.map_or(false, |(height, at)| {
+					height > head_height &&
+						now < at.saturating_add(SUBMISSION_LONGEVITY.unique_saturated_into())
+				})
+		}
+
+		/// The NEAR RPC client, with the endpoints configured for this node.
+		pub fn rpc_client() -> NearRpcClient {
+			NearRpcClient::with_overrides(T::RpcEndpoints::get(), T::ArchivalEndpoints::get())
+		}
+
+		/// Whether `block_producers` are the producers of the head's next epoch, which the head
+		/// commits to in `next_bp_hash`.
+		fn matches_next_bp_hash(
+			head: &LightClientBlockLiteView,
+			block_producers: &[ValidatorStakeView],
+		) -> bool {
+			CryptoHash::hash_borsh(block_producers) == head.inner_lite.next_bp_hash
+		}
+
+		/// Make `head` the light client head, and remember it in `RecentHeads`.
+		fn set_head(head: LightClientBlockLiteView) {
+			let height = head.inner_lite.height;
+			let recent_head = RecentHead {
+				hash: head.hash(),
+				outcome_root: head.inner_lite.outcome_root,
+				block_merkle_root: head.inner_lite.block_merkle_root,
+			};
+			LightClientHead::<T>::put(head);
+
+			if T::HeadHistory::get() == 0 {
+				return
+			}
+			RecentHeights::<T>::mutate(|heights| {
+				if heights.contains(&height) {
+					return
+				}
+				if heights.is_full() {
+					RecentHeads::<T>::remove(heights.remove(0));
+				}
+				// There is room after removing the oldest head.
+				let _ = heights.try_push(height);
+			});
+			RecentHeads::<T>::insert(height, recent_head);
+		}
+
+		/// Store the block producers of `epoch_id`, to be pruned after `EpochRetention` epochs.
+		fn store_block_producers(
+			epoch_id: CryptoHash,
+			block_producers: BoundedVec<ValidatorStakeView, ConstU32<MAX_BLOCK_PRODUCERS>>,
+		) {
+			BlockProducersByEpoch::<T>::insert(epoch_id, block_producers);
+			StoredEpochs::<T>::mutate(|epochs| {
+				if epochs.contains(&epoch_id) {
+					return
+				}
+				if epochs.is_full() {
+					BlockProducersByEpoch::<T>::remove(epochs.remove(0));
+				}
+				let _ = epochs.try_push(epoch_id);
+			});
+		}
+
+		/// The block producers stored for `epoch_id`, if any.
+		pub fn epoch_block_producers(epoch_id: &CryptoHash) -> Option<Vec<ValidatorStakeView>> {
+			BlockProducersByEpoch::<T>::get(epoch_id).map(BoundedVec::into_inner)
+		}
+
+		/// The `RelayerMode` set for this node, unsigned if none is.
+		pub fn relayer_mode() -> RelayerMode {
+			let mode = frame_support::sp_io::offchain::local_storage_get(
+				StorageKind::PERSISTENT,
+				RELAYER_MODE_KEY,
+			);
+			match mode {
+				Some(mode) => serde_json::from_slice(&mode).unwrap_or_else(|e| {
+					log::warn!("Invalid relayer mode: {}", e);
+					RelayerMode::default()
+				}),
+				None => RelayerMode::default(),
+			}
+		}
+
+		pub fn try_submit(relayer: T::Public, block_view: LightClientBlockView) -> DispatchResult {
 			// We retrieve a signer and check if it is valid.
-			//   Since this pallet only has one key in the keystore. We use `any_account()1 to
-			//   retrieve it. If there are multiple keys and we want to pinpoint it,
-			// `with_filter()` can be chained,   ref: https://substrate.dev/rustdocs/v3.0.0/frame_system/offchain/struct.Signer.html
-			let signer = Signer::<T, T::AuthorityId>::any_account();
+			//   Only the relayer's key is used, so that the submission is accepted in its turn,
+			//   ref: https://substrate.dev/rustdocs/v3.0.0/frame_system/offchain/struct.Signer.html
+			let signer = Signer::<T, T::AuthorityId>::any_account().with_filter(vec![relayer]);
 			frame_support::ensure!(
 				signer.can_sign(),
 				"No local accounts available. Consider adding one via author_insertKey RPC."
 			);
 
+			if Self::relayer_mode() == RelayerMode::Signed {
+				return signer
+					.send_signed_transaction(|_| Call::relay { block_view: block_view.clone() })
+					.ok_or("Failed to send request")
+					.and_then(|(_, res)| res.map_err(|()| "Failed to submit transaction"))
+					.map_err(|e| e.into())
+			}
+
 			signer
-				// .send_signed_transaction(|_acct| Call::submit {
-				// 	head: head.clone(),
-				// 	bps: bps.clone(),
-				// })
-				.submit_unsigned_transaction(Call::submit { head: head.clone(), bps: bps.clone() })
+				.send_unsigned_transaction(
+					|account| BlockViewPayload {
+						block_view: block_view.clone(),
+						public: account.public.clone(),
+					},
+					|payload, signature| Call::submit { payload, signature },
+				)
 				.ok_or("Failed to send request")
-				// .map(|x| x.1.unwrap())
-				.map(|x| x.unwrap())
+				.and_then(|(_, res)| res.map_err(|()| "Failed to submit transaction"))
 				.map_err(|e| e.into())
 		}
+
+		pub fn try_submit_headers(
+			relayer: T::Public,
+			block_views: Vec<LightClientBlockView>,
+		) -> DispatchResult {
+			let signer = Signer::<T, T::AuthorityId>::any_account().with_filter(vec![relayer]);
+			frame_support::ensure!(
+				signer.can_sign(),
+				"No local accounts available. Consider adding one via author_insertKey RPC."
+			);
+
+			signer
+				.send_unsigned_transaction(
+					|account| HeadersPayload {
+						block_views: block_views.clone(),
+						public: account.public.clone(),
+					},
+					|payload, signature| Call::submit_headers { payload, signature },
+				)
+				.ok_or("Failed to send request")
+				.and_then(|(_, res)| res.map_err(|()| "Failed to submit transaction"))
+				.map_err(|e| e.into())
+		}
+
+		pub fn try_submit_proof(
+			relayer: T::Public,
+			proof: RpcLightClientExecutionProofResponse,
+		) -> DispatchResult {
+			let signer = Signer::<T, T::AuthorityId>::any_account().with_filter(vec![relayer]);
+			frame_support::ensure!(
+				signer.can_sign(),
+				"No local accounts available. Consider adding one via author_insertKey RPC."
+			);
+
+			signer
+				.send_unsigned_transaction(
+					|account| ExecutionProofPayload {
+						proof: proof.clone(),
+						public: account.public.clone(),
+					},
+					|payload, signature| Call::submit_execution_proof { payload, signature },
+				)
+				.ok_or("Failed to send request")
+				.and_then(|(_, res)| res.map_err(|()| "Failed to submit transaction"))
+				.map_err(|e| e.into())
+		}
+
+		/// Pool validity of a submitted proof. Outcomes that are already verified are stale.
+		fn validate_execution_proof(
+			proof: &RpcLightClientExecutionProofResponse,
+		) -> TransactionValidity {
+			let id = proof.outcome_proof.id;
+			if VerifiedOutcomes::<T>::contains_key(id) {
+				return InvalidTransaction::Stale.into()
+			}
+
+			ValidTransaction::with_tag_prefix("NearExecutionProof")
+				.priority(T::UnsignedPriority::get())
+				// Only one proof per outcome makes it into the pool.
+				.and_provides(id)
+				.longevity(SUBMISSION_LONGEVITY)
+				.propagate(true)
+				.build()
+		}
+
+		/// Pool validity of `block_view` as the block after the head, as far as it can be
+		/// checked without verifying its approvals. Blocks that fail verification do not fail
+		/// `submit`, so they would otherwise take up block space for free.
+		fn precheck_block(
+			block_view: &LightClientBlockView,
+		) -> Result<(), TransactionValidityError> {
+			let head = LightClientHead::<T>::get().ok_or(InvalidTransaction::Call)?;
+			let state = LightClientState { head, next_bps: None };
+			let epoch_id = state.signing_epoch(block_view).map_err(|reason| match reason {
+				LightClientError::BlockAlreadyVerified { .. } => InvalidTransaction::Stale,
+				_ => InvalidTransaction::Custom(INVALID_BLOCK),
+			})?;
+			let bps = Self::epoch_block_producers(&epoch_id)
+				.ok_or(InvalidTransaction::Custom(INVALID_BLOCK))?;
+			check_claimed_stake(&block_view.approvals_after_next, &bps)
+				.map_err(|_| InvalidTransaction::Custom(INVALID_BLOCK).into())
+		}
+
+		/// Pool validity of a submitted block, based only on how it relates to the current head.
+		///
+		/// Full verification happens on dispatch, this only keeps stale and duplicate blocks out
+		/// of the pool.
+		fn validate_transaction_parameters(
+			block_view: &LightClientBlockView,
+		) -> TransactionValidity {
+			let head = LightClientHead::<T>::get().ok_or(InvalidTransaction::Call)?;
+			let height = block_view.inner_lite.height;
+			let head_height = head.inner_lite.height;
+			if height <= head_height {
+				return InvalidTransaction::Stale.into()
+			}
+
+			ValidTransaction::with_tag_prefix("NearLightClient")
+				// The further the block advances the head, the more useful it is.
+				.priority(T::UnsignedPriority::get().saturating_add(height - head_height))
+				// Only one block per height makes it into the pool.
+				.and_provides(height)
+				// The head moves on quickly, so there's no point keeping it around for long.
+				.longevity(SUBMISSION_LONGEVITY)
+				.propagate(true)
+				.build()
+		}
 	}
 }
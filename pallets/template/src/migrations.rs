@@ -0,0 +1,134 @@
+//! Storage migrations of the pallet.
+
+use crate::near::{
+	errors::{ParseKeyError, ParseViewError},
+	signature::{ED25519PublicKey, PublicKey, Secp256K1PublicKey},
+	types::Balance,
+	views::{ValidatorStakeV1, ValidatorStakeView},
+};
+use borsh::maybestd::string::String;
+use codec::{Decode, Encode};
+use sp_runtime::sp_std::prelude::*;
+
+/// How block producers were stored before the v1 storage migration.
+///
+/// The account id and the public key were kept as raw bytes, the key type being inferred from
+/// the key length.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct ValidatorStakeViewScaleHax {
+	pub account_id: Vec<u8>,
+	pub public_key: Vec<u8>,
+	pub stake: Balance,
+}
+
+impl TryFrom<ValidatorStakeViewScaleHax> for ValidatorStakeView {
+	type Error = ParseViewError;
+
+	fn try_from(value: ValidatorStakeViewScaleHax) -> Result<Self, Self::Error> {
+		let account_id = String::from_utf8(value.account_id)
+			.map_err(|_| ParseViewError::InvalidAccountId)?
+			.try_into()
+			.map_err(|_| ParseViewError::InvalidAccountId)?;
+		Ok(ValidatorStakeView::V1(ValidatorStakeV1 {
+			account_id,
+			public_key: match value.public_key.len() {
+				32 => PublicKey::ED25519(ED25519PublicKey::try_from(&value.public_key[..])?),
+				64 => PublicKey::SECP256K1(Secp256K1PublicKey::try_from(&value.public_key[..])?),
+				received_length => Err(ParseKeyError::InvalidLength {
+					expected_length: ed25519_dalek::PUBLIC_KEY_LENGTH,
+					received_length,
+				})?,
+			},
+			stake: value.stake,
+		}))
+	}
+}
+
+pub mod v1 {
+	use super::*;
+	use crate::{
+		near::hash::CryptoHash, BlockProducersByEpoch, Config, LightClientHead, Pallet,
+		StoredEpochs, MAX_BLOCK_PRODUCERS,
+	};
+	use frame_support::{
+		pallet_prelude::*,
+		traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+	};
+
+	/// Re-encodes the stored block producers from [`ValidatorStakeViewScaleHax`] to
+	/// [`ValidatorStakeView`].
+	///
+	/// Epochs whose block producers can not be parsed are dropped, they will have to be submitted
+	/// again. The others are indexed in `StoredEpochs` and pruned down to `EpochRetention`.
+	pub struct MigrateToV1<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+		fn on_runtime_upgrade() -> Weight {
+			let on_chain = Pallet::<T>::on_chain_storage_version();
+			if on_chain != 0 {
+				log::info!("Skipping v1 migration, storage is at {:?}", on_chain);
+				return T::DbWeight::get().reads(1)
+			}
+
+			let mut translated = 0u64;
+			let mut migrated = Vec::new();
+			BlockProducersByEpoch::<T>::translate::<
+				BoundedVec<ValidatorStakeViewScaleHax, ConstU32<MAX_BLOCK_PRODUCERS>>,
+				_,
+			>(|epoch_id, block_producers| {
+				translated += 1;
+				let views = block_producers
+					.into_iter()
+					.map(ValidatorStakeView::try_from)
+					.collect::<Result<Vec<_>, _>>()
+					.map_err(|e| {
+						log::warn!("Dropping block producers of epoch {}: {}", epoch_id, e)
+					})
+					.ok()?;
+				let views = BoundedVec::try_from(views).ok()?;
+				migrated.push(epoch_id);
+				Some(views)
+			});
+			let pruned = index_epochs::<T>(migrated);
+			StorageVersion::new(1).put::<Pallet<T>>();
+
+			log::info!(
+				"Migrated the block producers of {} epochs to v1, pruned {}",
+				translated,
+				pruned
+			);
+			T::DbWeight::get().reads_writes(translated + 3, translated + pruned + 2)
+		}
+	}
+
+	/// Index `migrated` in `StoredEpochs`, which only knows the epochs stored since it was
+	/// introduced, and prune the block producers down to `EpochRetention` epochs.
+	///
+	/// The order of the epochs that were not indexed is unknown, except that the head's epochs
+	/// are the latest. The others are taken to be older than the indexed ones.
+	///
+	/// Returns the number of pruned epochs.
+	fn index_epochs<T: Config>(migrated: Vec<CryptoHash>) -> u64 {
+		let head_epochs = LightClientHead::<T>::get()
+			.map(|head| vec![head.inner_lite.epoch_id, head.inner_lite.next_epoch_id])
+			.unwrap_or_default();
+		let indexed = StoredEpochs::<T>::get().into_inner();
+		let (latest, mut epochs): (Vec<_>, Vec<_>) = migrated
+			.into_iter()
+			.filter(|epoch_id| !indexed.contains(epoch_id))
+			.partition(|epoch_id| head_epochs.contains(epoch_id));
+		epochs.extend(indexed);
+		for epoch_id in head_epochs {
+			if latest.contains(&epoch_id) && !epochs.contains(&epoch_id) {
+				epochs.push(epoch_id);
+			}
+		}
+
+		let excess = epochs.len().saturating_sub(T::EpochRetention::get() as usize);
+		for epoch_id in epochs.drain(..excess) {
+			BlockProducersByEpoch::<T>::remove(epoch_id);
+		}
+		StoredEpochs::<T>::put(BoundedVec::truncate_from(epochs));
+		excess as u64
+	}
+}
@@ -1,11 +1,17 @@
-use crate as pallet_template;
-use frame_support::traits::{ConstU16, ConstU64};
+use crate::{
+	self as pallet_template,
+	near::client::{NEAR_RPC_ARCHIVE_ENDPOINT, NEAR_RPC_ENDPOINT},
+};
+use frame_support::{
+	parameter_types,
+	traits::{ConstU16, ConstU32, ConstU64, GenesisBuild},
+};
 use frame_system as system;
-use sp_core::H256;
+use sp_core::{sr25519::Signature, Pair, H256};
 use sp_runtime::{
-	offchain::{testing, OffchainWorkerExt},
-	testing::Header,
-	traits::{BlakeTwo256, IdentityLookup},
+	offchain::{testing, OffchainDbExt, OffchainWorkerExt},
+	testing::{Header, TestXt},
+	traits::{BlakeTwo256, Extrinsic as ExtrinsicT, IdentifyAccount, IdentityLookup, Verify},
 };
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
@@ -19,10 +25,27 @@ frame_support::construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		System: frame_system,
+		Balances: pallet_balances,
 		TemplateModule: pallet_template,
 	}
 );
 
+pub type Extrinsic = TestXt<RuntimeCall, ()>;
+pub type AccountId = <<Signature as Verify>::Signer as IdentifyAccount>::AccountId;
+
+parameter_types! {
+	pub const UnsignedPriority: u64 = 1 << 20;
+	pub const RpcEndpoints: &'static [&'static str] = &[NEAR_RPC_ENDPOINT];
+	pub const ArchivalEndpoints: &'static [&'static str] = &[NEAR_RPC_ARCHIVE_ENDPOINT];
+	pub const ProofRequestDeposit: u64 = 10;
+	pub const MaxProofRequests: u32 = 4;
+	pub const ProofRequestsPerBlock: u32 = 2;
+	pub const ProofRequestTtl: u64 = 10;
+	pub RewardPot: AccountId = reward_pot();
+	pub const HeaderReward: u64 = 5;
+	pub const EpochReward: u64 = 20;
+}
+
 impl system::Config for Test {
 	type BaseCallFilter = frame_support::traits::Everything;
 	type BlockWeights = ();
@@ -34,31 +57,127 @@ impl system::Config for Test {
 	type BlockNumber = u64;
 	type Hash = H256;
 	type Hashing = BlakeTwo256;
-	type AccountId = u64;
+	type AccountId = AccountId;
 	type Lookup = IdentityLookup<Self::AccountId>;
 	type Header = Header;
 	type RuntimeEvent = RuntimeEvent;
 	type BlockHashCount = ConstU64<250>;
 	type Version = ();
 	type PalletInfo = PalletInfo;
-	type AccountData = ();
+	type AccountData = pallet_balances::AccountData<u64>;
 	type OnNewAccount = ();
 	type OnKilledAccount = ();
 	type SystemWeightInfo = ();
 	type SS58Prefix = ConstU16<42>;
 	type OnSetCode = ();
-	type MaxConsumers = frame_support::traits::ConstU32<16>;
+	type MaxConsumers = ConstU32<16>;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type Balance = u64;
+	type RuntimeEvent = RuntimeEvent;
+	type DustRemoval = ();
+	type ExistentialDeposit = ConstU64<1>;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+impl frame_system::offchain::SigningTypes for Test {
+	type Public = <Signature as Verify>::Signer;
+	type Signature = Signature;
+}
+
+impl<LocalCall> frame_system::offchain::SendTransactionTypes<LocalCall> for Test
+where
+	RuntimeCall: From<LocalCall>,
+{
+	type OverarchingCall = RuntimeCall;
+	type Extrinsic = Extrinsic;
+}
+
+impl<LocalCall> frame_system::offchain::CreateSignedTransaction<LocalCall> for Test
+where
+	RuntimeCall: From<LocalCall>,
+{
+	fn create_transaction<C: frame_system::offchain::AppCrypto<Self::Public, Self::Signature>>(
+		call: RuntimeCall,
+		_public: <Signature as Verify>::Signer,
+		_account: AccountId,
+		nonce: u64,
+	) -> Option<(RuntimeCall, <Extrinsic as ExtrinsicT>::SignaturePayload)> {
+		Some((call, (nonce, ())))
+	}
 }
 
 impl pallet_template::Config for Test {
 	type RuntimeEvent = RuntimeEvent;
+	type AuthorityId = pallet_template::crypto::TestAuthId;
+	type Call = RuntimeCall;
+	type UnsignedPriority = UnsignedPriority;
+	type RpcEndpoints = RpcEndpoints;
+	type ArchivalEndpoints = ArchivalEndpoints;
+	type Currency = Balances;
+	type ProofRequestDeposit = ProofRequestDeposit;
+	type MaxProofRequests = MaxProofRequests;
+	type ProofRequestsPerBlock = ProofRequestsPerBlock;
+	type ProofRequestTtl = ProofRequestTtl;
+	type OnOutcomeVerified = ();
+	type AdminOrigin = frame_system::EnsureRoot<AccountId>;
+	type EpochRetention = ConstU32<3>;
+	type HeadHistory = ConstU32<2>;
+	type RewardPot = RewardPot;
+	type HeaderReward = HeaderReward;
+	type EpochReward = EpochReward;
+	type MaxRelayers = ConstU32<3>;
+	type RelayerTurn = ConstU64<2>;
+	type MaxHeadersPerBatch = ConstU32<4>;
+}
+
+/// The account endowed with `ENDOWMENT` at genesis.
+pub fn requester() -> AccountId {
+	sp_core::sr25519::Public::from_raw([7; 32])
+}
+
+pub const ENDOWMENT: u64 = 100;
+
+/// The account relayer rewards are paid from, endowed with `ENDOWMENT` at genesis.
+pub fn reward_pot() -> AccountId {
+	sp_core::sr25519::Public::from_raw([9; 32])
+}
+
+/// The relayer registered at genesis, whose key signs the test submissions.
+pub fn relayer() -> AccountId {
+	sp_core::sr25519::Pair::from_seed(&[1; 32]).public()
 }
 
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-	let (offchain, state) = testing::TestOffchainExt::new();
-	let mut t: sp_io::TestExternalities =
-		system::GenesisConfig::default().build_storage::<Test>().unwrap().into();
+	test_ext(pallet_template::GenesisConfig::<Test>::default())
+}
+
+/// Like `new_test_ext`, with the light client anchored at `fixtures/3_previous_epoch.json`.
+pub fn new_test_ext_anchored() -> sp_io::TestExternalities {
+	let json = std::fs::read("fixtures/3_previous_epoch.json").unwrap();
+	test_ext(pallet_template::GenesisConfig::<Test>::from_json_rpc(&json).unwrap())
+}
+
+fn test_ext(light_client: pallet_template::GenesisConfig<Test>) -> sp_io::TestExternalities {
+	let (offchain, _state) = testing::TestOffchainExt::new();
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(requester(), ENDOWMENT), (reward_pot(), ENDOWMENT)],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+	pallet_template::GenesisConfig::<Test> { relayers: vec![relayer()], ..light_client }
+		.assimilate_storage(&mut t)
+		.unwrap();
+	let mut t: sp_io::TestExternalities = t.into();
+	t.register_extension(OffchainDbExt::new(offchain.clone()));
 	t.register_extension(OffchainWorkerExt::new(offchain));
+	t.execute_with(|| System::set_block_number(1));
 	t
 }
@@ -1,11 +1,15 @@
-use crate::near::views::LightClientBlockView;
+use crate::near::{
+	errors::{NearErrorCause, NearRpcError},
+	proof::{RpcLightClientExecutionProofResponse, TransactionOrReceiptId},
+	views::LightClientBlockView,
+};
 use borsh::maybestd::string::String;
 use codec::alloc::string::ToString;
 use serde::{Deserialize, Serialize};
 use sp_runtime::{
 	offchain::{
-		http::{Method, Request},
-		Duration,
+		http::{self, Method, Request},
+		Duration, StorageKind,
 	},
 	sp_std::{prelude::*, vec},
 };
@@ -14,9 +18,15 @@ mod rpc;
 
 pub const NEAR_RPC_ENDPOINT: &str = "https://rpc.mainnet.near.org";
 pub const NEAR_RPC_ARCHIVE_ENDPOINT: &str = "https://archival-rpc.mainnet.near.org";
+/// Offchain local storage key overriding the RPC endpoints, as a comma separated list of URLs.
+pub const RPC_ENDPOINTS_KEY: &[u8] = b"near::rpc-endpoints";
+/// Offchain local storage key overriding the archival endpoints, as a comma separated list of
+/// URLs.
+pub const ARCHIVAL_ENDPOINTS_KEY: &[u8] = b"near::archival-endpoints";
 const FETCH_TIMEOUT_PERIOD: u64 = 30000; // in milli-seconds
-const LOCK_TIMEOUT_EXPIRATION: u64 = FETCH_TIMEOUT_PERIOD + 1000; // in milli-seconds
-const LOCK_BLOCK_EXPIRATION: u32 = 3; // in block number
+/// How long a worker holds the sync lock at most, in case it dies while holding it.
+pub const LOCK_TIMEOUT_EXPIRATION: u64 = FETCH_TIMEOUT_PERIOD + 1000; // in milli-seconds
+pub const LOCK_BLOCK_EXPIRATION: u32 = 3; // in block number
 
 #[derive(Deserialize, Serialize, Default)]
 pub struct JsonRpcRequest {
@@ -46,6 +56,39 @@ pub struct JsonRpcResult {
 	id: String,
 }
 
+/// The `error` of a JSON-RPC response, in NEAR's structured format.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct JsonRpcError {
+	/// The kind of error, e.g. `HANDLER_ERROR` or `REQUEST_VALIDATION_ERROR`.
+	#[serde(default)]
+	pub name: Option<String>,
+	#[serde(default)]
+	pub cause: Option<JsonRpcErrorCause>,
+	pub code: i64,
+	pub message: String,
+	/// Legacy, unstructured description of the error.
+	#[serde(default)]
+	pub data: Option<serde_json::Value>,
+}
+
+/// The `cause` of a NEAR JSON-RPC error.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct JsonRpcErrorCause {
+	#[serde(flatten)]
+	pub name: NearErrorCause,
+	/// The details of the cause, e.g. the `block_reference` of an `UNKNOWN_BLOCK`.
+	#[serde(default)]
+	pub info: Option<serde_json::Value>,
+}
+
+/// A JSON-RPC response, with either a `result` or an `error`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonRpcResponse {
+	Result(JsonRpcResult),
+	Error { error: JsonRpcError },
+}
+
 impl From<NearRpcResult> for JsonRpcResult {
 	fn from(result: NearRpcResult) -> Self {
 		Self { jsonrpc: "2.0".to_string(), result, id: "pallet-near".to_string() }
@@ -56,16 +99,45 @@ impl From<NearRpcResult> for JsonRpcResult {
 #[serde(untagged)]
 pub enum NearRpcResult {
 	NextBlock(LightClientBlockView),
-	ExperimentalLightClientProof(LightClientBlockView),
+	ExperimentalLightClientProof(RpcLightClientExecutionProofResponse),
+	/// There is no block newer than the requested one yet, NEAR answers with an empty result.
+	NoNewerBlock(EmptyResult),
 }
 
-#[derive(Deserialize, Serialize)]
+/// The empty `{}` result of a JSON-RPC response. Any other object is not empty, so a malformed
+/// result fails to decode rather than being taken for an empty one.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct EmptyResult {}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
 #[serde(untagged)]
 pub enum LightClientProofParams {
 	Transaction { transaction_hash: String, sender_id: String },
 	Receipt { receipt_id: String, receiver_id: String },
 }
 
+impl From<TransactionOrReceiptId> for LightClientProofParams {
+	fn from(id: TransactionOrReceiptId) -> Self {
+		match id {
+			TransactionOrReceiptId::Transaction { hash, sender } =>
+				Self::Transaction { transaction_hash: hash.to_string(), sender_id: sender.into() },
+			TransactionOrReceiptId::Receipt { id, receiver } =>
+				Self::Receipt { receipt_id: id.to_string(), receiver_id: receiver.into() },
+		}
+	}
+}
+
+impl LightClientProofParams {
+	/// The `type` of the `EXPERIMENTAL_light_client_proof` request.
+	pub fn kind(&self) -> &'static str {
+		match self {
+			Self::Transaction { .. } => "transaction",
+			Self::Receipt { .. } => "receipt",
+		}
+	}
+}
+
 #[derive(Deserialize, Serialize)]
 #[serde(untagged)]
 pub enum NearRpcRequestParams {
@@ -96,14 +168,64 @@ impl NearRpcRequestParams {
 	}
 }
 
-pub struct NearRpcClient;
+/// Client for the NEAR JSON-RPC, trying each endpoint in order until one of them answers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NearRpcClient {
+	/// Endpoints serving recent data, such as the next light client block.
+	pub rpc_endpoints: Vec<String>,
+	/// Endpoints serving historical data, such as outcome proofs.
+	pub archival_endpoints: Vec<String>,
+}
+
+/// Parse a comma separated list of endpoints, skipping empty entries.
+pub fn parse_endpoints(endpoints: &[u8]) -> Option<Vec<String>> {
+	let endpoints: Vec<String> = core::str::from_utf8(endpoints)
+		.ok()?
+		.split(',')
+		.map(str::trim)
+		.filter(|endpoint| !endpoint.is_empty())
+		.map(ToString::to_string)
+		.collect();
+	(!endpoints.is_empty()).then_some(endpoints)
+}
 
 impl NearRpcClient {
-	pub fn build_request(&self, body: &JsonRpcRequest) -> Request<Vec<Vec<u8>>> {
-		let endpoint = match body.params {
-			NearRpcRequestParams::NextBlock { .. } => NEAR_RPC_ENDPOINT,
-			NearRpcRequestParams::ExperimentalLightClientProof { .. } => NEAR_RPC_ARCHIVE_ENDPOINT,
+	pub fn new(rpc_endpoints: &[&str], archival_endpoints: &[&str]) -> Self {
+		Self {
+			rpc_endpoints: rpc_endpoints.iter().map(ToString::to_string).collect(),
+			archival_endpoints: archival_endpoints.iter().map(ToString::to_string).collect(),
+		}
+	}
+
+	/// A client using the endpoints set in offchain local storage, falling back to the given
+	/// defaults for any kind that is not set.
+	///
+	/// Operators can set the endpoints with the `offchain_localStorageSet` RPC, using the
+	/// `PERSISTENT` kind and `RPC_ENDPOINTS_KEY` or `ARCHIVAL_ENDPOINTS_KEY`.
+	pub fn with_overrides(rpc_endpoints: &[&str], archival_endpoints: &[&str]) -> Self {
+		let mut client = Self::new(rpc_endpoints, archival_endpoints);
+		let local_endpoints = |key| {
+			frame_support::sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, key)
+				.and_then(|endpoints| parse_endpoints(&endpoints))
 		};
+		if let Some(endpoints) = local_endpoints(RPC_ENDPOINTS_KEY) {
+			client.rpc_endpoints = endpoints;
+		}
+		if let Some(endpoints) = local_endpoints(ARCHIVAL_ENDPOINTS_KEY) {
+			client.archival_endpoints = endpoints;
+		}
+		client
+	}
+
+	/// The endpoints that can serve `params`, in order of preference.
+	pub fn endpoints(&self, params: &NearRpcRequestParams) -> &[String] {
+		match params {
+			NearRpcRequestParams::NextBlock { .. } => &self.rpc_endpoints,
+			NearRpcRequestParams::ExperimentalLightClientProof { .. } => &self.archival_endpoints,
+		}
+	}
+
+	pub fn build_request(&self, endpoint: &str, body: &JsonRpcRequest) -> Request<Vec<Vec<u8>>> {
 		Request::default()
 			.method(Method::Post)
 			.url(endpoint)
@@ -111,11 +233,82 @@ impl NearRpcClient {
 			.add_header("Content-Type", "application/json")
 	}
 
-	pub fn fetch_latest_header(&self, latest_verified: &str) -> LightClientBlockView {
-		let request = self.build_request(
-			&NearRpcRequestParams::NextBlock { last_block_hash: latest_verified.to_string() }
-				.into(),
-		);
+	/// The next light client block after `latest_verified`, if there is one yet.
+	pub fn fetch_latest_header(
+		&self,
+		latest_verified: &str,
+	) -> Result<Option<LightClientBlockView>, NearRpcError> {
+		self.fetch_next_block(&self.rpc_endpoints, latest_verified)
+	}
+
+	/// Like `fetch_latest_header`, for when `latest_verified` is too old for the RPC endpoints.
+	pub fn fetch_archived_header(
+		&self,
+		latest_verified: &str,
+	) -> Result<Option<LightClientBlockView>, NearRpcError> {
+		self.fetch_next_block(&self.archival_endpoints, latest_verified)
+	}
+
+	fn fetch_next_block(
+		&self,
+		endpoints: &[String],
+		latest_verified: &str,
+	) -> Result<Option<LightClientBlockView>, NearRpcError> {
+		let body =
+			NearRpcRequestParams::NextBlock { last_block_hash: latest_verified.to_string() }.into();
+
+		match self.fetch(endpoints, &body)?.result {
+			NearRpcResult::NextBlock(block) => Ok(Some(block)),
+			NearRpcResult::NoNewerBlock(EmptyResult {}) => Ok(None),
+			_ => Err(NearRpcError::UnexpectedResponse),
+		}
+	}
+
+	/// The proof of the outcome of the transaction or receipt in `params`, against the light
+	/// client head `light_client_head`.
+	pub fn fetch_execution_proof(
+		&self,
+		params: LightClientProofParams,
+		light_client_head: &str,
+	) -> Result<RpcLightClientExecutionProofResponse, NearRpcError> {
+		let body: JsonRpcRequest = NearRpcRequestParams::ExperimentalLightClientProof {
+			kind: params.kind().to_string(),
+			params,
+			light_client_head: light_client_head.to_string(),
+		}
+		.into();
+
+		match self.fetch(self.endpoints(&body.params), &body)?.result {
+			NearRpcResult::ExperimentalLightClientProof(proof) => Ok(proof),
+			_ => Err(NearRpcError::UnexpectedResponse),
+		}
+	}
+
+	/// Send `body` to each endpoint that can serve it, until one of them answers.
+	fn fetch(
+		&self,
+		endpoints: &[String],
+		body: &JsonRpcRequest,
+	) -> Result<JsonRpcResult, NearRpcError> {
+		let mut result = Err(NearRpcError::NoEndpoints);
+		for endpoint in endpoints {
+			result = self.fetch_from(endpoint, body);
+			match &result {
+				Err(e) if e.is_endpoint_failure() => {
+					log::warn!("Request to {} failed: {}, trying the next endpoint", endpoint, e)
+				},
+				_ => break,
+			}
+		}
+		result
+	}
+
+	fn fetch_from(
+		&self,
+		endpoint: &str,
+		body: &JsonRpcRequest,
+	) -> Result<JsonRpcResult, NearRpcError> {
+		let request = self.build_request(endpoint, body);
 
 		// Keeping the offchain worker execution time reasonable, so limiting the call to be
 		// within 3s.
@@ -127,9 +320,8 @@ impl NearRpcClient {
 			.send() // Sending the request out by the host
 			.map_err(|e| {
 				log::info!("{:?}", e);
-				// <Error<T>>::HttpFetchingError
-			})
-			.unwrap();
+				NearRpcError::Io
+			})?;
 
 		// By default, the http request is async from the runtime perspective. So we are asking
 		// the   runtime to wait here
@@ -137,29 +329,32 @@ impl NearRpcClient {
 		// two `?`   ref: https://docs.substrate.io/rustdocs/latest/sp_runtime/offchain/http/struct.PendingRequest.html#method.try_wait
 		let response = pending
 			.try_wait(timeout)
+			.map_err(|_| NearRpcError::DeadlineReached)?
 			.map_err(|e| {
 				log::info!("{:?}", e);
-				// <Error<T>>::HttpFetchingError
-			})
-			.unwrap()
-			.map_err(|e| {
-				log::info!("{:?}", e);
-				// <Error<T>>::HttpFetchingError
-			})
-			.unwrap();
+				if matches!(e, http::Error::DeadlineReached) {
+					NearRpcError::DeadlineReached
+				} else {
+					NearRpcError::Io
+				}
+			})?;
 
 		if response.code != 200 {
-			log::info!("Unexpected http request status code: {}", response.code);
-			// return Err(<Error<T>>::HttpFetchingError)
+			return Err(NearRpcError::HttpStatus(response.code))
 		}
 
 		let resp_bytes = response.body().collect::<Vec<u8>>();
-		let resp_str = core::str::from_utf8(&resp_bytes).unwrap();
-		let res: JsonRpcResult = serde_json::from_str(&resp_str).unwrap();
-		if let NearRpcResult::NextBlock(block) = res.result {
-			block
-		} else {
-			panic!("Unexpected response from near rpc");
+		match serde_json::from_slice(&resp_bytes)
+			.map_err(|e| NearRpcError::Decode(e.to_string()))?
+		{
+			JsonRpcResponse::Result(result) => Ok(result),
+			JsonRpcResponse::Error { error } => {
+				let (cause, info) = match error.cause {
+					Some(JsonRpcErrorCause { name, info }) => (Some(name), info),
+					None => (None, None),
+				};
+				Err(NearRpcError::JsonRpc { code: error.code, message: error.message, cause, info })
+			},
 		}
 	}
 }
@@ -205,6 +400,22 @@ mod tests {
 		)
 	}
 
+	#[test]
+	fn test_proof_params_from_id() {
+		let hash: crate::near::hash::CryptoHash =
+			"5TGZe4jsuUGx9A65HNuEMkb3J4vW6Wo2pxDbyzYFrDeC".parse().unwrap();
+		assert_eq!(
+			LightClientProofParams::from(TransactionOrReceiptId::Receipt {
+				id: hash,
+				receiver: "alice.near".parse().unwrap()
+			}),
+			LightClientProofParams::Receipt {
+				receipt_id: "5TGZe4jsuUGx9A65HNuEMkb3J4vW6Wo2pxDbyzYFrDeC".to_string(),
+				receiver_id: "alice.near".to_string(),
+			}
+		);
+	}
+
 	#[test]
 	fn test_serialize_tx_proof_correctly() {
 		let request = NearRpcRequestParams::ExperimentalLightClientProof {
@@ -240,8 +451,8 @@ mod tests {
 			}
 			.into();
 
-			let request = NearRpcClient
-				.build_request(&request_body)
+			let request = NearRpcClient::default()
+				.build_request(NEAR_RPC_ENDPOINT, &request_body)
 				.send() // Sending the request out by the host
 				.unwrap();
 
@@ -273,6 +484,273 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn test_parse_endpoints() {
+		assert_eq!(
+			parse_endpoints(b"http://localhost:3030, https://rpc.testnet.near.org,,"),
+			Some(vec![
+				"http://localhost:3030".to_string(),
+				"https://rpc.testnet.near.org".to_string()
+			])
+		);
+		assert_eq!(parse_endpoints(b" , "), None);
+		assert_eq!(parse_endpoints(&[0xff]), None);
+	}
+
+	#[test]
+	fn endpoints_are_overridden_by_local_storage() {
+		let mut t = new_test_ext();
+		let (offchain, _) = TestOffchainExt::with_offchain_db(t.offchain_db());
+		t.register_extension(OffchainDbExt::new(offchain.clone()));
+		t.register_extension(OffchainWorkerExt::new(offchain));
+
+		t.execute_with(|| {
+			let client =
+				NearRpcClient::with_overrides(&[NEAR_RPC_ENDPOINT], &[NEAR_RPC_ARCHIVE_ENDPOINT]);
+			assert_eq!(
+				client,
+				NearRpcClient::new(&[NEAR_RPC_ENDPOINT], &[NEAR_RPC_ARCHIVE_ENDPOINT])
+			);
+
+			sp_io::offchain::local_storage_set(
+				StorageKind::PERSISTENT,
+				RPC_ENDPOINTS_KEY,
+				b"http://localhost:3030",
+			);
+			let client =
+				NearRpcClient::with_overrides(&[NEAR_RPC_ENDPOINT], &[NEAR_RPC_ARCHIVE_ENDPOINT]);
+			assert_eq!(
+				client,
+				NearRpcClient::new(&["http://localhost:3030"], &[NEAR_RPC_ARCHIVE_ENDPOINT])
+			);
+		});
+	}
+
+	#[test]
+	fn falls_back_to_the_next_endpoint() {
+		let mut t = new_test_ext();
+		let (offchain, offchain_state) = TestOffchainExt::with_offchain_db(t.offchain_db());
+		t.register_extension(OffchainDbExt::new(offchain.clone()));
+		t.register_extension(OffchainWorkerExt::new(offchain));
+
+		let last_block_hash = "2rs9o3B6nAQ3pEfVcBQdLnBqZrfpVuZJeKC8FpTshhua";
+		expect_next_block_request(
+			&offchain_state,
+			"http://dead",
+			last_block_hash,
+			b"Bad Gateway".to_vec(),
+		);
+		expect_next_block_request(
+			&offchain_state,
+			NEAR_RPC_ENDPOINT,
+			last_block_hash,
+			serde_json::to_vec(&get_response()).unwrap(),
+		);
+
+		t.execute_with(|| {
+			let client = NearRpcClient::new(&["http://dead", NEAR_RPC_ENDPOINT], &[]);
+			let block = client.fetch_latest_header(last_block_hash).unwrap().unwrap();
+			if let NearRpcResult::NextBlock(expected) = get_response().result {
+				assert_eq!(block, expected);
+			}
+		});
+	}
+
+	fn expect_next_block_request(
+		offchain_state: &parking_lot::RwLock<testing::OffchainState>,
+		uri: &str,
+		last_block_hash: &str,
+		response: Vec<u8>,
+	) {
+		let request_body: JsonRpcRequest =
+			NearRpcRequestParams::NextBlock { last_block_hash: last_block_hash.to_string() }.into();
+		offchain_state.write().expect_request(testing::PendingRequest {
+			method: "POST".into(),
+			uri: uri.into(),
+			headers: vec![("Content-Type".into(), "application/json".into())],
+			body: serde_json::to_vec(&request_body).unwrap(),
+			response: Some(response),
+			sent: true,
+			..Default::default()
+		});
+	}
+
+	#[test]
+	fn returns_json_rpc_errors_without_falling_back() {
+		let mut t = new_test_ext();
+		let (offchain, offchain_state) = TestOffchainExt::with_offchain_db(t.offchain_db());
+		t.register_extension(OffchainWorkerExt::new(offchain));
+
+		let last_block_hash = "2rs9o3B6nAQ3pEfVcBQdLnBqZrfpVuZJeKC8FpTshhua";
+		expect_next_block_request(
+			&offchain_state,
+			NEAR_RPC_ENDPOINT,
+			last_block_hash,
+			br#"{"jsonrpc":"2.0","error":{"name":"HANDLER_ERROR","cause":{"info":{"block_reference":{"block_id":"2rs9o3B6nAQ3pEfVcBQdLnBqZrfpVuZJeKC8FpTshhua"}},"name":"GARBAGE_COLLECTED_BLOCK"},"code":-32000,"message":"Server error","data":"DB Not Found Error"},"id":"pallet-near"}"#.to_vec(),
+		);
+
+		t.execute_with(|| {
+			let client = NearRpcClient::new(&[NEAR_RPC_ENDPOINT, "http://unused"], &[]);
+			assert_eq!(
+				client.fetch_latest_header(last_block_hash),
+				Err(NearRpcError::JsonRpc {
+					code: -32000,
+					message: "Server error".into(),
+					cause: Some(NearErrorCause::GarbageCollectedBlock),
+					info: Some(serde_json::json!({
+						"block_reference": { "block_id": last_block_hash }
+					})),
+				})
+			);
+		});
+	}
+
+	#[test]
+	fn no_newer_block_is_not_an_error() {
+		let mut t = new_test_ext();
+		let (offchain, offchain_state) = TestOffchainExt::with_offchain_db(t.offchain_db());
+		t.register_extension(OffchainWorkerExt::new(offchain));
+
+		let last_block_hash = "2rs9o3B6nAQ3pEfVcBQdLnBqZrfpVuZJeKC8FpTshhua";
+		expect_next_block_request(
+			&offchain_state,
+			NEAR_RPC_ARCHIVE_ENDPOINT,
+			last_block_hash,
+			br#"{"jsonrpc":"2.0","result":{},"id":"pallet-near"}"#.to_vec(),
+		);
+
+		t.execute_with(|| {
+			let client = NearRpcClient::new(&[], &[NEAR_RPC_ARCHIVE_ENDPOINT]);
+			assert_eq!(client.fetch_archived_header(last_block_hash), Ok(None));
+		});
+	}
+
+	#[test]
+	fn rejects_malformed_next_blocks() {
+		let mut t = new_test_ext();
+		let (offchain, offchain_state) = TestOffchainExt::with_offchain_db(t.offchain_db());
+		t.register_extension(OffchainWorkerExt::new(offchain));
+
+		let last_block_hash = "2rs9o3B6nAQ3pEfVcBQdLnBqZrfpVuZJeKC8FpTshhua";
+		expect_next_block_request(
+			&offchain_state,
+			NEAR_RPC_ENDPOINT,
+			last_block_hash,
+			br#"{"jsonrpc":"2.0","result":{"inner_lite":{}},"id":"pallet-near"}"#.to_vec(),
+		);
+
+		t.execute_with(|| {
+			let client = NearRpcClient::new(&[NEAR_RPC_ENDPOINT], &[]);
+			assert!(matches!(
+				client.fetch_latest_header(last_block_hash),
+				Err(NearRpcError::Decode(_))
+			));
+		});
+	}
+
+	#[test]
+	fn fetches_execution_proofs_from_the_archive() {
+		let mut t = new_test_ext();
+		let (offchain, offchain_state) = TestOffchainExt::with_offchain_db(t.offchain_db());
+		t.register_extension(OffchainWorkerExt::new(offchain));
+
+		let mut head: crate::near::views::LightClientBlockLiteView =
+			if let NearRpcResult::NextBlock(block) = get_response().result {
+				block.into()
+			} else {
+				unreachable!()
+			};
+		let proof = crate::near::proof::tests::synthetic_proof(&mut head);
+		let params = LightClientProofParams::Transaction {
+			transaction_hash: "5TGZe4jsuUGx9A65HNuEMkb3J4vW6Wo2pxDbyzYFrDeC".to_string(),
+			sender_id: "alice.near".to_string(),
+		};
+		let request_body: JsonRpcRequest = NearRpcRequestParams::ExperimentalLightClientProof {
+			kind: "transaction".to_string(),
+			params: params.clone(),
+			light_client_head: "14gQvvYkY2MrKxikmSoEF5nmgwnrQZqU6kmfxdaSSS88".to_string(),
+		}
+		.into();
+		offchain_state.write().expect_request(testing::PendingRequest {
+			method: "POST".into(),
+			uri: NEAR_RPC_ARCHIVE_ENDPOINT.into(),
+			headers: vec![("Content-Type".into(), "application/json".into())],
+			body: serde_json::to_vec(&request_body).unwrap(),
+			response: Some(
+				serde_json::to_vec(&JsonRpcResult::from(
+					NearRpcResult::ExperimentalLightClientProof(proof.clone()),
+				))
+				.unwrap(),
+			),
+			sent: true,
+			..Default::default()
+		});
+
+		t.execute_with(|| {
+			let client = NearRpcClient::new(&[NEAR_RPC_ENDPOINT], &[NEAR_RPC_ARCHIVE_ENDPOINT]);
+			assert_eq!(
+				client
+					.fetch_execution_proof(params, "14gQvvYkY2MrKxikmSoEF5nmgwnrQZqU6kmfxdaSSS88"),
+				Ok(proof)
+			);
+		});
+	}
+
+	#[test]
+	fn test_deserialize_error_causes() {
+		let error: JsonRpcError = serde_json::from_str(
+			r#"{"name":"HANDLER_ERROR","cause":{"info":{},"name":"UNKNOWN_BLOCK"},"code":-32000,"message":"Server error"}"#,
+		)
+		.unwrap();
+		assert_eq!(error.name.as_deref(), Some("HANDLER_ERROR"));
+		assert_eq!(
+			error.cause,
+			Some(JsonRpcErrorCause {
+				name: NearErrorCause::UnknownBlock,
+				info: Some(serde_json::json!({}))
+			})
+		);
+
+		let error: JsonRpcError = serde_json::from_str(
+			r#"{"name":"HANDLER_ERROR","cause":{"name":"SOMETHING_NEW"},"code":-32000,"message":"Server error"}"#,
+		)
+		.unwrap();
+		assert_eq!(
+			error.cause,
+			Some(JsonRpcErrorCause { name: NearErrorCause::Other, info: None })
+		);
+
+		let error: JsonRpcError =
+			serde_json::from_str(r#"{"code":-32601,"message":"Method not found"}"#).unwrap();
+		assert_eq!(error.cause, None);
+	}
+
+	#[test]
+	fn returns_the_last_endpoint_failure() {
+		let mut t = new_test_ext();
+		let (offchain, offchain_state) = TestOffchainExt::with_offchain_db(t.offchain_db());
+		t.register_extension(OffchainWorkerExt::new(offchain));
+
+		let last_block_hash = "2rs9o3B6nAQ3pEfVcBQdLnBqZrfpVuZJeKC8FpTshhua";
+		expect_next_block_request(
+			&offchain_state,
+			NEAR_RPC_ENDPOINT,
+			last_block_hash,
+			b"Bad Gateway".to_vec(),
+		);
+
+		t.execute_with(|| {
+			let client = NearRpcClient::new(&[NEAR_RPC_ENDPOINT], &[]);
+			assert!(matches!(
+				client.fetch_latest_header(last_block_hash),
+				Err(NearRpcError::Decode(_))
+			));
+
+			let client = NearRpcClient::new(&[], &[]);
+			assert_eq!(client.fetch_latest_header(last_block_hash), Err(NearRpcError::NoEndpoints));
+		});
+	}
+
 	pub(crate) fn new_test_ext() -> sp_io::TestExternalities {
 		frame_system::GenesisConfig::default()
 			.build_storage::<crate::mock::Test>()
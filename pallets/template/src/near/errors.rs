@@ -1,7 +1,10 @@
 use borsh::maybestd::string::String;
 use sp_runtime::sp_std::prelude::*;
 
-use super::types::AccountId;
+use super::{
+	hash::CryptoHash,
+	types::{AccountId, Balance, BlockHeight},
+};
 
 #[derive(Debug, Clone, thiserror_no_std::Error)]
 pub enum ParseKeyTypeError {
@@ -65,8 +68,172 @@ impl From<ParseKeyTypeError> for ParseSignatureError {
 	}
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, thiserror_no_std::Error)]
+pub enum ParseAccountError {
+	#[error("account id must be 2 to 64 characters long, not {length}")]
+	InvalidLength { length: usize },
+	#[error("account id has an invalid character '{char}' at index {index}")]
+	InvalidChar { char: char, index: usize },
+	#[error("account id has a redundant separator '{char}' at index {index}")]
+	RedundantSeparator { char: char, index: usize },
+}
+
+/// Errors converting the legacy `ValidatorStakeViewScaleHax` views, stored before the v1 storage
+/// migration, back into NEAR views.
+#[derive(Debug, Clone, thiserror_no_std::Error)]
+pub enum ParseViewError {
+	#[error("account id is not valid")]
+	InvalidAccountId,
+	#[error("invalid public key: {0}")]
+	InvalidPublicKey(ParseKeyError),
+}
+
+impl From<ParseKeyError> for ParseViewError {
+	fn from(err: ParseKeyError) -> Self {
+		Self::InvalidPublicKey(err)
+	}
+}
+
 #[derive(Debug, Clone, thiserror_no_std::Error)]
 pub enum ImplicitPublicKeyError {
 	#[error("'{account_id}' is not an implicit account")]
 	AccountIsNotImplicit { account_id: AccountId },
 }
+
+/// Reasons a light client block is rejected, one per check performed by
+/// `LightClientState::validate_and_update_head`.
+#[derive(
+	Debug,
+	Clone,
+	PartialEq,
+	Eq,
+	codec::Encode,
+	codec::Decode,
+	scale_info::TypeInfo,
+	thiserror_no_std::Error,
+)]
+pub enum LightClientError {
+	/// (1) The block is not newer than the current head.
+	#[error("block {height} is not newer than head {head_height}")]
+	BlockAlreadyVerified { height: BlockHeight, head_height: BlockHeight },
+	/// (2) The block is neither in the epoch of the head nor in the next one.
+	#[error("block is not in the current or next epoch")]
+	InvalidEpoch,
+	/// (3) The block is in the next epoch but does not carry the next block producers.
+	#[error("block is in the next epoch but has no next block producers")]
+	NextBlockProducersMissing,
+	/// (4) An approval was not signed by the corresponding block producer.
+	#[error("approval of block producer {index} has an invalid signature")]
+	InvalidSignature { index: u32 },
+	/// (5) The approved stake does not exceed two thirds of the total stake.
+	#[error("approved stake {approved} of {total} is not enough")]
+	InsufficientApprovedStake { approved: Balance, total: Balance },
+	/// (6) The next block producers do not hash to `next_bp_hash`.
+	#[error("next block producers hash is invalid")]
+	InvalidNextBlockProducersHash,
+	/// (4) There are fewer approvals than block producers.
+	#[error("{approvals} approvals for {block_producers} block producers")]
+	MissingApprovals { approvals: u32, block_producers: u32 },
+	/// (5) The stake of the block producers does not fit in a `Balance`.
+	#[error("total stake overflows")]
+	StakeOverflow,
+}
+
+/// Reasons an execution outcome proof is rejected by `proof::verify_execution_proof`.
+#[derive(
+	Debug,
+	Clone,
+	PartialEq,
+	Eq,
+	codec::Encode,
+	codec::Decode,
+	scale_info::TypeInfo,
+	thiserror_no_std::Error,
+)]
+pub enum ProofError {
+	/// The outcome does not hash up to the outcome root of the block it claims to be in.
+	#[error("outcome root {computed} does not match the block outcome root {expected}")]
+	InvalidOutcomeRoot { expected: CryptoHash, computed: CryptoHash },
+	/// The block is not part of the block merkle tree of the trusted head.
+	#[error("block {block_hash} is not included in the head's block merkle root")]
+	InvalidBlockProof { block_hash: CryptoHash },
+}
+
+/// The cause of a NEAR JSON-RPC error, from the `cause.name` of the error object.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "name", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum NearErrorCause {
+	/// The node does not know the block, e.g. it was produced before the node's history.
+	UnknownBlock,
+	/// The node no longer has the block, an archival node does.
+	GarbageCollectedBlock,
+	/// The node timed out handling the request.
+	TimeoutError,
+	/// The epoch of the block is not known to the node.
+	EpochOutOfBounds,
+	/// The transaction or receipt is not known to the node.
+	UnknownTransactionOrReceipt,
+	/// The block of the outcome is not final yet.
+	NotConfirmed,
+	/// The node does not track the shard of the outcome.
+	UnavailableShard,
+	/// The node failed internally.
+	InternalError,
+	/// Any cause not listed here.
+	#[serde(other)]
+	Other,
+}
+
+/// Errors talking to the NEAR JSON-RPC.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror_no_std::Error)]
+pub enum NearRpcError {
+	/// There are no endpoints configured for the request.
+	#[error("no endpoints configured")]
+	NoEndpoints,
+	/// The request did not complete before its deadline.
+	#[error("request deadline reached")]
+	DeadlineReached,
+	/// The request could not be sent or its response could not be read.
+	#[error("request failed")]
+	Io,
+	/// The endpoint answered with a status other than 200.
+	#[error("unexpected http status {0}")]
+	HttpStatus(u16),
+	/// The endpoint answered with a JSON-RPC error object.
+	#[error("json-rpc error {code}: {message} ({cause:?}: {info:?})")]
+	JsonRpc {
+		code: i64,
+		message: String,
+		cause: Option<NearErrorCause>,
+		/// The details of the cause, in a format that depends on it.
+		info: Option<serde_json::Value>,
+	},
+	/// The response could not be decoded.
+	#[error("failed to decode response: {0}")]
+	Decode(String),
+	/// The response is valid but not of the kind that was requested.
+	#[error("unexpected response")]
+	UnexpectedResponse,
+}
+
+impl NearRpcError {
+	/// Whether the error is down to the endpoint rather than the request, so another endpoint
+	/// may succeed.
+	pub fn is_endpoint_failure(&self) -> bool {
+		matches!(self, Self::DeadlineReached | Self::Io | Self::HttpStatus(_) | Self::Decode(_))
+	}
+
+	/// The cause given by NEAR, if the endpoint answered with an error object.
+	pub fn cause(&self) -> Option<&NearErrorCause> {
+		match self {
+			Self::JsonRpc { cause, .. } => cause.as_ref(),
+			_ => None,
+		}
+	}
+
+	/// Whether the request timed out, either on our side or on the node.
+	pub fn is_timeout(&self) -> bool {
+		matches!(self, Self::DeadlineReached | Self::HttpStatus(408)) ||
+			self.cause() == Some(&NearErrorCause::TimeoutError)
+	}
+}
@@ -20,6 +20,7 @@ use sp_runtime::sp_std::{prelude::*, vec};
 	borsh::BorshSerialize,
 	codec::Encode,
 	codec::Decode,
+	codec::MaxEncodedLen,
 	scale_info::TypeInfo,
 )]
 #[as_ref(forward)]
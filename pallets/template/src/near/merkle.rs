@@ -11,6 +11,9 @@ use sp_runtime::sp_std::{prelude::*, vec};
 	BorshDeserialize,
 	serde::Serialize,
 	serde::Deserialize,
+	codec::Encode,
+	codec::Decode,
+	scale_info::TypeInfo,
 )]
 pub struct MerklePathItem {
 	pub hash: MerkleHash,
@@ -28,6 +31,9 @@ pub type MerklePath = Vec<MerklePathItem>;
 	BorshDeserialize,
 	serde::Serialize,
 	serde::Deserialize,
+	codec::Encode,
+	codec::Decode,
+	scale_info::TypeInfo,
 )]
 pub enum Direction {
 	Left,
@@ -109,10 +115,13 @@ pub fn verify_path<T: BorshSerialize>(root: MerkleHash, path: &MerklePath, item:
 	verify_hash(root, path, CryptoHash::hash_borsh(item))
 }
 
+/// Verify merkle path for an item that is already hashed, such as a block hash.
 pub fn verify_hash(root: MerkleHash, path: &MerklePath, item_hash: MerkleHash) -> bool {
 	compute_root_from_path(path, item_hash) == root
 }
 
+/// Fold `path` into `item_hash` from the leaf up. `Direction` is the side the sibling in the path
+/// is on, so `Left` hashes the sibling first.
 pub fn compute_root_from_path(path: &MerklePath, item_hash: MerkleHash) -> MerkleHash {
 	let mut res = item_hash;
 	for item in path {
@@ -257,6 +266,77 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn test_compute_root_from_path_directions() {
+		let leaf = CryptoHash::hash_bytes(b"leaf");
+		let sibling = CryptoHash::hash_bytes(b"sibling");
+		assert_eq!(compute_root_from_path(&vec![], leaf), leaf);
+		assert_eq!(
+			compute_root_from_path(
+				&vec![MerklePathItem { hash: sibling, direction: Direction::Left }],
+				leaf
+			),
+			combine_hash(&sibling, &leaf)
+		);
+		assert_eq!(
+			compute_root_from_path(
+				&vec![MerklePathItem { hash: sibling, direction: Direction::Right }],
+				leaf
+			),
+			combine_hash(&leaf, &sibling)
+		);
+	}
+
+	#[test]
+	fn test_verify_hash() {
+		let items = vec![111, 222, 333, 444, 555];
+		let (root, paths) = merklize(&items);
+		for (item, path) in items.iter().zip(&paths) {
+			let item_hash = CryptoHash::hash_borsh(item);
+			assert!(verify_hash(root, path, item_hash));
+			assert_eq!(compute_root_from_path_and_item(path, item), root);
+			assert!(!verify_hash(CryptoHash::default(), path, item_hash));
+		}
+	}
+
+	/// Compute the path of `hashes[index]` in the tree built by `compute_root`.
+	fn compute_path(hashes: &[CryptoHash], index: usize) -> MerklePath {
+		if hashes.len() == 1 {
+			return vec![]
+		}
+		let subtree_len = hashes.len().next_power_of_two() / 2;
+		if index < subtree_len {
+			let mut path = compute_path(&hashes[..subtree_len], index);
+			path.push(MerklePathItem {
+				hash: compute_root(&hashes[subtree_len..]),
+				direction: Direction::Right,
+			});
+			path
+		} else {
+			let mut path = compute_path(&hashes[subtree_len..], index - subtree_len);
+			path.push(MerklePathItem {
+				hash: compute_root(&hashes[..subtree_len]),
+				direction: Direction::Left,
+			});
+			path
+		}
+	}
+
+	#[test]
+	fn test_merkle_tree_inclusion() {
+		let mut tree = PartialMerkleTree::default();
+		let mut hashes = vec![];
+		for i in 0..33 {
+			let cur_hash = CryptoHash::hash_bytes(&[i]);
+			hashes.push(cur_hash);
+			tree.insert(cur_hash);
+			for (j, hash) in hashes.iter().enumerate() {
+				assert!(verify_hash(tree.root(), &compute_path(&hashes, j), *hash));
+			}
+		}
+		assert!(!verify_hash(tree.root(), &compute_path(&hashes, 0), hashes[1]));
+	}
+
 	#[test]
 	fn test_combine_hash_stability() {
 		let a = MerkleHash::default();
@@ -2,10 +2,13 @@ use crate::near::{block_header::BlockHeaderInnerLite, hash::sha256};
 
 use self::{
 	block_header::ApprovalInner,
+	errors::LightClientError,
 	hash::CryptoHash,
+	signature::Signature,
+	types::{Balance, BlockHeight},
 	views::{
 		BlockHeaderInnerLiteView, LightClientBlockLiteView, LightClientBlockView,
-		ValidatorStakeView, ValidatorStakeViewScaleHax,
+		ValidatorStakeView,
 	},
 };
 use crate::near::hash::borsh as borshit;
@@ -27,7 +30,20 @@ pub mod views;
 #[derive(Debug, Clone, Encode, Decode, scale_info::TypeInfo)]
 pub struct LightClientState {
 	pub head: LightClientBlockLiteView,
-	pub next_bps: Option<(CryptoHash, Vec<ValidatorStakeViewScaleHax>)>,
+	pub next_bps: Option<(CryptoHash, Vec<ValidatorStakeView>)>,
+}
+
+/// The outcome of a successful `LightClientState::validate_and_update_head`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeadUpdate {
+	/// Height of the head before the update.
+	pub previous_height: BlockHeight,
+	/// Height of the new head.
+	pub height: BlockHeight,
+	/// Hash of the new head.
+	pub hash: CryptoHash,
+	/// The epoch whose block producers were learned from the new head, if any.
+	pub next_bps_epoch: Option<CryptoHash>,
 }
 
 macro_rules! cvec {
@@ -81,17 +97,20 @@ impl LightClientState {
 		(*current_block_hash.as_bytes(), next_block_hash.into(), approval_message)
 	}
 
-	pub fn validate_and_update_head(
-		&mut self,
+	/// The epoch whose block producers must have approved `block_view`, if it can follow the
+	/// head: it is either in the head's epoch, or in the next one when it announces the block
+	/// producers of the epoch after.
+	pub fn signing_epoch(
+		&self,
 		block_view: &LightClientBlockView,
-		epoch_block_producers: Vec<ValidatorStakeView>,
-	) -> bool {
-		let (_, _, approval_message) = self.reconstruct_light_client_block_view_fields(block_view);
-
+	) -> Result<CryptoHash, LightClientError> {
 		// (1) The block was already verified
 		if block_view.inner_lite.height <= self.head.inner_lite.height {
 			log::info!("Block has already been verified");
-			return false
+			return Err(LightClientError::BlockAlreadyVerified {
+				height: block_view.inner_lite.height,
+				head_height: self.head.inner_lite.height,
+			})
 		}
 
 		// (2)
@@ -99,7 +118,7 @@ impl LightClientState {
 			.contains(&block_view.inner_lite.epoch_id)
 		{
 			log::info!("Block is not in the current or next epoch");
-			return false
+			return Err(LightClientError::InvalidEpoch)
 		}
 
 		// (3) Same as next epoch and no new set, covering N + 2
@@ -107,52 +126,53 @@ impl LightClientState {
 			block_view.next_bps.is_none()
 		{
 			log::info!("Block is in the next epoch but no new set");
-			return false
+			return Err(LightClientError::NextBlockProducersMissing)
 		}
 
-		// (4) and (5)
-		let mut total_stake = 0;
-		let mut approved_stake = 0;
+		Ok(block_view.inner_lite.epoch_id)
+	}
 
-		for (maybe_signature, block_producer) in
-			block_view.approvals_after_next.iter().zip(epoch_block_producers.iter())
-		{
-			total_stake += block_producer.stake();
-
-			if let Some(signature) = maybe_signature {
-				log::debug!(
-					"Checking if signature {} and message {:?} was signed by {}",
-					signature,
-					approval_message,
-					block_producer.public_key()
-				);
-				approved_stake += block_producer.stake();
-				if !signature.verify(&approval_message, &block_producer.public_key()) {
-					log::warn!("Signature is invalid");
-					return false
-				}
-			}
-		}
+	/// Verify `block_view` against the head and the block producers of its epoch, as selected by
+	/// [`Self::signing_epoch`], and make it the new head.
+	pub fn validate_and_update_head(
+		&mut self,
+		block_view: &LightClientBlockView,
+		epoch_block_producers: Vec<ValidatorStakeView>,
+	) -> Result<HeadUpdate, LightClientError> {
+		let (current_block_hash, _, approval_message) =
+			self.reconstruct_light_client_block_view_fields(block_view);
+
+		// (1), (2) and (3)
+		self.signing_epoch(block_view)?;
+
+		// (4) and (5)
+		let (approved_stake, total_stake) = approved_stake(
+			&block_view.approvals_after_next,
+			&epoch_block_producers,
+			&approval_message,
+		)?;
 		log::debug!("All signatures are valid");
 
-		let threshold = total_stake * 2 / 3;
-		if approved_stake <= threshold {
+		if !exceeds_two_thirds(approved_stake, total_stake) {
 			log::warn!("Not enough stake approved");
-			return false
+			return Err(LightClientError::InsufficientApprovedStake {
+				approved: approved_stake,
+				total: total_stake,
+			})
 		}
 
 		// (6)
+		let mut next_bps_epoch = None;
 		if let Some(next_bps) = &block_view.next_bps {
 			let next_bps_hash = CryptoHash::hash_borsh(&next_bps);
 			if next_bps_hash != block_view.inner_lite.next_bp_hash {
 				log::warn!("Next block producers hash is invalid");
-				return false
+				return Err(LightClientError::InvalidNextBlockProducersHash)
 			}
 
-			self.next_bps = Some((
-				self.head.inner_lite.next_epoch_id,
-				next_bps.into_iter().map(|s| s.clone().into()).collect(),
-			));
+			// The block producers of a block are those of the epoch after it.
+			next_bps_epoch = Some(block_view.inner_lite.next_epoch_id);
+			self.next_bps = Some((block_view.inner_lite.next_epoch_id, next_bps.clone()));
 		}
 
 		let prev_head = self.head.inner_lite.height;
@@ -160,8 +180,104 @@ impl LightClientState {
 		let new_head = self.head.inner_lite.height;
 		log::info!("prev/current head: {}/{}", prev_head, new_head);
 
-		true
+		Ok(HeadUpdate {
+			previous_height: prev_head,
+			height: new_head,
+			hash: CryptoHash(current_block_hash),
+			next_bps_epoch,
+		})
+	}
+}
+
+/// Checks the approvals of `block_producers` and returns the approved and the total stake.
+///
+/// There must be an approval, possibly empty, for each block producer. Approvals past the last
+/// block producer are ignored: at epoch boundaries they come from validators of the next epoch,
+/// which do not count towards finality in this one.
+fn approved_stake(
+	approvals: &[Option<Signature>],
+	block_producers: &[ValidatorStakeView],
+	approval_message: &[u8],
+) -> Result<(Balance, Balance), LightClientError> {
+	if approvals.len() < block_producers.len() {
+		return Err(LightClientError::MissingApprovals {
+			approvals: approvals.len() as u32,
+			block_producers: block_producers.len() as u32,
+		})
+	}
+
+	let mut total_stake: Balance = 0;
+	let mut approved_stake: Balance = 0;
+	for (index, (maybe_signature, block_producer)) in
+		approvals.iter().zip(block_producers).enumerate()
+	{
+		total_stake = total_stake
+			.checked_add(block_producer.stake())
+			.ok_or(LightClientError::StakeOverflow)?;
+
+		if let Some(signature) = maybe_signature {
+			log::debug!(
+				"Checking if signature {} and message {:?} was signed by {}",
+				signature,
+				approval_message,
+				block_producer.public_key()
+			);
+			if !signature.verify(approval_message, block_producer.public_key()) {
+				log::warn!("Signature is invalid");
+				return Err(LightClientError::InvalidSignature { index: index as u32 })
+			}
+			// Never more than the total stake, which did not overflow.
+			approved_stake += block_producer.stake();
+		}
 	}
+	Ok((approved_stake, total_stake))
+}
+
+/// Checks that `approvals` can finalize a block of `block_producers`, without verifying them:
+/// there is an approval for each block producer, and those that signed hold more than two
+/// thirds of the stake.
+///
+/// This is cheap enough to screen blocks before they are fully verified with
+/// [`LightClientState::validate_and_update_head`].
+pub fn check_claimed_stake(
+	approvals: &[Option<Signature>],
+	block_producers: &[ValidatorStakeView],
+) -> Result<(), LightClientError> {
+	if approvals.len() < block_producers.len() {
+		return Err(LightClientError::MissingApprovals {
+			approvals: approvals.len() as u32,
+			block_producers: block_producers.len() as u32,
+		})
+	}
+
+	let mut total_stake: Balance = 0;
+	let mut claimed_stake: Balance = 0;
+	for (maybe_signature, block_producer) in approvals.iter().zip(block_producers) {
+		total_stake = total_stake
+			.checked_add(block_producer.stake())
+			.ok_or(LightClientError::StakeOverflow)?;
+		if maybe_signature.is_some() {
+			// Never more than the total stake, which did not overflow.
+			claimed_stake += block_producer.stake();
+		}
+	}
+
+	if !exceeds_two_thirds(claimed_stake, total_stake) {
+		return Err(LightClientError::InsufficientApprovedStake {
+			approved: claimed_stake,
+			total: total_stake,
+		})
+	}
+	Ok(())
+}
+
+/// Whether `approved` is more than two thirds of `total`, exactly `approved * 3 > total * 2`.
+///
+/// As `approved` is part of `total`, this is `approved > 2 * (total - approved)`, which only
+/// overflows when the unapproved stake is more than half of all stake anyway.
+pub fn exceeds_two_thirds(approved: Balance, total: Balance) -> bool {
+	let unapproved = total.saturating_sub(approved);
+	unapproved.checked_mul(2).map_or(false, |unapproved| approved > unapproved)
 }
 
 #[cfg(test)]
@@ -174,9 +290,14 @@ mod tests {
 		views::BlockHeaderInnerLiteView,
 		*,
 	};
-	use crate::near::signature::{KeyType, Signature};
+	use crate::near::{
+		signature::{KeyType, PublicKey, SecretKey, Signature},
+		views::ValidatorStakeV1,
+	};
 	use borsh::{BorshDeserialize, BorshSerialize};
+	use codec::MaxEncodedLen;
 	use ed25519_dalek::Verifier;
+	use rand::{rngs::StdRng, Rng, SeedableRng};
 	use serde_json;
 	use sp_core::bytes::from_hex;
 
@@ -244,15 +365,45 @@ mod tests {
 	}
 
 	#[test]
-	fn test_deserialize_into_hax_correctly() {
-		let bps = get_next_bps();
+	fn test_block_view_scale_roundtrip() {
+		let block_view = get_current();
+
+		let encoded = block_view.encode();
+		let decoded = LightClientBlockView::decode(&mut &encoded[..]).unwrap();
+
+		assert_eq!(decoded, block_view);
+	}
+
+	#[test]
+	fn test_rejects_malformed_scale() {
+		let validator = get_next_bps()[0].clone().unwrap_v1();
+
+		let mut encoded = validator.public_key.encode();
+		encoded[0] = 2;
+		assert!(PublicKey::decode(&mut &encoded[..]).is_err());
 
-		let hax: Vec<ValidatorStakeViewScaleHax> = bps.iter().map(|s| s.clone().into()).collect();
+		let mut encoded = get_current().approvals_after_next[0].clone().unwrap().encode();
+		encoded[0] = 2;
+		assert!(Signature::decode(&mut &encoded[..]).is_err());
 
-		let bps_again: Vec<ValidatorStakeView> =
-			hax.iter().map(|s| s.clone().into()).collect::<Vec<_>>();
+		let encoded = "a".repeat(types::MAX_ACCOUNT_ID_LEN + 1).encode();
+		assert!(types::AccountId::decode(&mut &encoded[..]).is_err());
+	}
+
+	#[test]
+	fn test_validator_stake_scale_roundtrip() {
+		let bps = get_next_bps();
 
+		let encoded = bps.encode();
+		let bps_again = Vec::<ValidatorStakeView>::decode(&mut &encoded[..]).unwrap();
 		assert_eq!(bps, bps_again);
+
+		// Keys and signatures are SCALE encoded like in borsh.
+		let validator = bps[0].clone().unwrap_v1();
+		assert_eq!(validator.public_key.encode(), validator.public_key.try_to_vec().unwrap());
+		let signature = get_current().approvals_after_next[0].clone().unwrap();
+		assert_eq!(signature.encode(), signature.try_to_vec().unwrap());
+		assert!(signature.encode().len() <= Signature::max_encoded_len());
 	}
 
 	fn get_epochs() -> Vec<(u32, LightClientBlockView, CryptoHash)> {
@@ -282,17 +433,7 @@ mod tests {
 
 		let mut state = LightClientState {
 			head: headers_by_epoch[0].1.clone().into(),
-			next_bps: Some((
-				next_epoch_id,
-				headers_by_epoch[0]
-					.1
-					.next_bps
-					.clone()
-					.unwrap()
-					.into_iter()
-					.map(Into::into)
-					.collect(),
-			)),
+			next_bps: Some((next_epoch_id, headers_by_epoch[0].1.next_bps.clone().unwrap())),
 		};
 
 		let (current, _, approval_message) =
@@ -301,11 +442,11 @@ mod tests {
 
 		let signature = headers_by_epoch[1].1.approvals_after_next[0].clone().unwrap();
 		if let Signature::ED25519(signature) = signature {
+			// The block is signed by the producers announced by the last block of the previous
+			// epoch.
 			let first_validator = &get_previous_previous().next_bps.unwrap()[0];
 			log::info!("first_validator: {:?}", first_validator);
 
-			// FIXME: need to get these, currently verifying against the next bps is why this test
-			// fails
 			let signer = first_validator.public_key().unwrap_as_ed25519();
 			let signer = ed25519_dalek::PublicKey::from_bytes(&signer.0).unwrap();
 
@@ -315,32 +456,302 @@ mod tests {
 		}
 	}
 
-	#[test]
-	fn test_validate() {
+	fn get_state_at_first_epoch() -> LightClientState {
 		let headers_by_epoch = get_epochs();
 		let next_epoch_id = headers_by_epoch[1].1.inner_lite.epoch_id.clone();
 
-		let mut state = LightClientState {
+		LightClientState {
 			head: headers_by_epoch[0].1.clone().into(),
-			next_bps: Some((
-				next_epoch_id,
-				headers_by_epoch[0]
-					.1
-					.next_bps
-					.clone()
-					.unwrap()
-					.into_iter()
-					.map(Into::into)
-					.collect(),
-			)),
-		};
+			next_bps: Some((next_epoch_id, headers_by_epoch[0].1.next_bps.clone().unwrap())),
+		}
+	}
+
+	#[test]
+	fn test_validate() {
+		let headers_by_epoch = get_epochs();
+		let mut state = get_state_at_first_epoch();
+
+		let update = state
+			.validate_and_update_head(
+				&headers_by_epoch[1].1.clone(),
+				headers_by_epoch[0].1.next_bps.clone().unwrap(),
+			)
+			.unwrap();
+
+		assert_eq!(update.previous_height, headers_by_epoch[0].1.inner_lite.height);
+		assert_eq!(update.height, headers_by_epoch[1].1.inner_lite.height);
+		assert_eq!(update.hash, headers_by_epoch[1].2);
+		assert_eq!(update.next_bps_epoch, Some(headers_by_epoch[1].1.inner_lite.next_epoch_id));
+	}
+
+	#[test]
+	fn test_follows_epoch_transitions() {
+		let headers_by_epoch = get_epochs();
+		let anchor = &headers_by_epoch[0].1;
+		let mut state = LightClientState { head: anchor.clone().into(), next_bps: None };
+		let mut bps_by_epoch =
+			vec![(anchor.inner_lite.next_epoch_id, anchor.next_bps.clone().unwrap())];
+
+		for (_, block_view, hash) in &headers_by_epoch[1..] {
+			let epoch_id = state.signing_epoch(block_view).unwrap();
+			assert_eq!(epoch_id, state.head.inner_lite.next_epoch_id);
+			let bps = bps_by_epoch.iter().find(|(epoch, _)| *epoch == epoch_id).unwrap().1.clone();
+
+			let update = state.validate_and_update_head(block_view, bps).unwrap();
+			assert_eq!(update.hash, *hash);
+			assert_eq!(update.next_bps_epoch, Some(block_view.inner_lite.next_epoch_id));
+			assert_eq!(state.head, block_view.clone().into());
+
+			let (epoch, next_bps) = state.next_bps.take().unwrap();
+			bps_by_epoch.push((epoch, next_bps));
+		}
+	}
+
+	#[test]
+	fn test_rejects_producers_of_another_epoch() {
+		let headers_by_epoch = get_epochs();
+		let mut state =
+			LightClientState { head: headers_by_epoch[1].1.clone().into(), next_bps: None };
+
+		assert!(state
+			.validate_and_update_head(
+				&headers_by_epoch[2].1,
+				headers_by_epoch[0].1.next_bps.clone().unwrap()
+			)
+			.is_err());
+		assert_eq!(state.head, headers_by_epoch[1].1.clone().into());
+	}
+
+	#[test]
+	fn test_rejects_already_verified() {
+		let headers_by_epoch = get_epochs();
+		let mut state = get_state_at_first_epoch();
+		let bps = headers_by_epoch[0].1.next_bps.clone().unwrap();
+
+		assert!(state.validate_and_update_head(&headers_by_epoch[1].1, bps.clone()).is_ok());
+		assert_eq!(
+			state.validate_and_update_head(&headers_by_epoch[1].1, bps),
+			Err(LightClientError::BlockAlreadyVerified {
+				height: headers_by_epoch[1].1.inner_lite.height,
+				head_height: headers_by_epoch[1].1.inner_lite.height,
+			})
+		);
+	}
+
+	#[test]
+	fn test_rejects_invalid_epoch() {
+		let headers_by_epoch = get_epochs();
+		let mut state = get_state_at_first_epoch();
+		let mut block_view = headers_by_epoch[1].1.clone();
+		block_view.inner_lite.epoch_id = CryptoHash::default();
+
+		assert_eq!(
+			state.validate_and_update_head(
+				&block_view,
+				headers_by_epoch[0].1.next_bps.clone().unwrap()
+			),
+			Err(LightClientError::InvalidEpoch)
+		);
+	}
+
+	#[test]
+	fn test_rejects_missing_next_bps() {
+		let headers_by_epoch = get_epochs();
+		let mut state = get_state_at_first_epoch();
+		let mut block_view = headers_by_epoch[1].1.clone();
+		block_view.next_bps = None;
+
+		assert_eq!(
+			state.validate_and_update_head(
+				&block_view,
+				headers_by_epoch[0].1.next_bps.clone().unwrap()
+			),
+			Err(LightClientError::NextBlockProducersMissing)
+		);
+	}
+
+	#[test]
+	fn test_rejects_invalid_signature() {
+		let headers_by_epoch = get_epochs();
+		let mut state = get_state_at_first_epoch();
+		let mut block_view = headers_by_epoch[1].1.clone();
+
+		let signed: Vec<usize> = block_view
+			.approvals_after_next
+			.iter()
+			.enumerate()
+			.filter_map(|(i, s)| s.as_ref().map(|_| i))
+			.collect();
+		block_view.approvals_after_next[signed[0]] =
+			block_view.approvals_after_next[signed[1]].clone();
+
+		assert_eq!(
+			state.validate_and_update_head(
+				&block_view,
+				headers_by_epoch[0].1.next_bps.clone().unwrap()
+			),
+			Err(LightClientError::InvalidSignature { index: signed[0] as u32 })
+		);
+	}
 
-		assert!(state.validate_and_update_head(
-			&headers_by_epoch[1].1.clone(),
-			headers_by_epoch[0].1.next_bps.clone().unwrap(),
+	#[test]
+	fn test_rejects_insufficient_stake() {
+		let headers_by_epoch = get_epochs();
+		let mut state = get_state_at_first_epoch();
+		let mut block_view = headers_by_epoch[1].1.clone();
+		block_view.approvals_after_next.iter_mut().for_each(|s| *s = None);
+
+		assert!(matches!(
+			state.validate_and_update_head(
+				&block_view,
+				headers_by_epoch[0].1.next_bps.clone().unwrap()
+			),
+			Err(LightClientError::InsufficientApprovedStake { approved: 0, .. })
 		));
 	}
 
+	#[test]
+	fn test_rejects_missing_approvals() {
+		let headers_by_epoch = get_epochs();
+		let mut state = get_state_at_first_epoch();
+		let mut block_view = headers_by_epoch[1].1.clone();
+		block_view.approvals_after_next.pop();
+
+		assert_eq!(
+			state.validate_and_update_head(
+				&block_view,
+				headers_by_epoch[0].1.next_bps.clone().unwrap()
+			),
+			Err(LightClientError::MissingApprovals { approvals: 99, block_producers: 100 })
+		);
+	}
+
+	#[test]
+	fn test_ignores_approvals_past_block_producers() {
+		let headers_by_epoch = get_epochs();
+		let mut state = get_state_at_first_epoch();
+		let mut block_view = headers_by_epoch[1].1.clone();
+		let extra = SecretKey::from_random(KeyType::ED25519).sign(b"not the approval message");
+		block_view.approvals_after_next.push(Some(extra));
+
+		assert!(state
+			.validate_and_update_head(&block_view, headers_by_epoch[0].1.next_bps.clone().unwrap())
+			.is_ok());
+	}
+
+	#[test]
+	fn test_claimed_stake() {
+		let headers_by_epoch = get_epochs();
+		let block_producers = headers_by_epoch[0].1.next_bps.clone().unwrap();
+		let mut block_view = headers_by_epoch[1].1.clone();
+		assert_eq!(check_claimed_stake(&block_view.approvals_after_next, &block_producers), Ok(()));
+
+		// Signatures are not verified.
+		let forged = SecretKey::from_random(KeyType::ED25519).sign(b"not the approval message");
+		block_view.approvals_after_next.iter_mut().for_each(|s| {
+			if s.is_some() {
+				*s = Some(forged.clone())
+			}
+		});
+		assert_eq!(check_claimed_stake(&block_view.approvals_after_next, &block_producers), Ok(()));
+
+		block_view.approvals_after_next.iter_mut().for_each(|s| *s = None);
+		assert!(matches!(
+			check_claimed_stake(&block_view.approvals_after_next, &block_producers),
+			Err(LightClientError::InsufficientApprovedStake { approved: 0, .. })
+		));
+		assert_eq!(
+			check_claimed_stake(&block_view.approvals_after_next[1..], &block_producers),
+			Err(LightClientError::MissingApprovals { approvals: 99, block_producers: 100 })
+		);
+	}
+
+	fn block_producer(secret_key: &SecretKey, stake: Balance) -> ValidatorStakeView {
+		ValidatorStakeView::V1(ValidatorStakeV1 {
+			account_id: "validator.near".parse().unwrap(),
+			public_key: secret_key.public_key(),
+			stake,
+		})
+	}
+
+	#[test]
+	fn test_approved_stake_of_random_distributions() {
+		let mut rng: StdRng = SeedableRng::seed_from_u64(1);
+		let message = b"approval";
+		for _ in 0..20 {
+			let len = rng.gen_range(1, 50);
+			// Stakes of up to 2^100 so that the reference below does not overflow.
+			let max_stake = 1u128 << rng.gen_range(1, 100);
+			let keys: Vec<_> = (0..len).map(|_| SecretKey::from_random(KeyType::ED25519)).collect();
+			let block_producers: Vec<_> = keys
+				.iter()
+				.map(|key| block_producer(key, rng.gen_range(0, max_stake)))
+				.collect();
+			let approvals: Vec<_> =
+				keys.iter().map(|key| rng.gen_bool(0.7).then(|| key.sign(message))).collect();
+
+			let (approved, total) = approved_stake(&approvals, &block_producers, message).unwrap();
+			let stakes = block_producers.iter().zip(&approvals);
+			assert_eq!(total, stakes.clone().map(|(bp, _)| bp.stake()).sum::<Balance>());
+			assert_eq!(
+				approved,
+				stakes
+					.filter(|(_, approval)| approval.is_some())
+					.map(|(bp, _)| bp.stake())
+					.sum()
+			);
+			assert_eq!(exceeds_two_thirds(approved, total), approved * 3 > total * 2);
+		}
+	}
+
+	#[test]
+	fn test_rejects_stake_overflow() {
+		let keys: Vec<_> = (0..3).map(|_| SecretKey::from_random(KeyType::ED25519)).collect();
+		let block_producers: Vec<_> =
+			keys.iter().map(|key| block_producer(key, Balance::MAX / 2)).collect();
+
+		assert_eq!(
+			approved_stake(&[None, None, None], &block_producers, b"approval"),
+			Err(LightClientError::StakeOverflow)
+		);
+		assert_eq!(
+			approved_stake(&[None, None], &block_producers[1..], b"approval"),
+			Ok((0, Balance::MAX - 1))
+		);
+	}
+
+	#[test]
+	fn test_two_thirds_threshold_is_exact() {
+		let mut rng: StdRng = SeedableRng::seed_from_u64(1);
+		let totals = (0..1000).map(|_| rng.gen::<Balance>() >> rng.gen_range(0, 128));
+		for total in totals.chain([0, 1, 2, 3, Balance::MAX - 1, Balance::MAX]) {
+			// The least stake that is more than two thirds of the total.
+			let threshold = total / 3 * 2 + total % 3 * 2 / 3 + 1;
+			assert!(!exceeds_two_thirds(threshold - 1, total), "{} of {}", threshold - 1, total);
+			if threshold <= total {
+				assert!(exceeds_two_thirds(threshold, total), "{} of {}", threshold, total);
+				assert!(exceeds_two_thirds(total, total));
+			}
+		}
+	}
+
+	#[test]
+	fn test_rejects_invalid_next_bps_hash() {
+		let headers_by_epoch = get_epochs();
+		let mut state = get_state_at_first_epoch();
+		let mut block_view = headers_by_epoch[1].1.clone();
+		block_view.next_bps.as_mut().unwrap().pop();
+
+		assert_eq!(
+			state.validate_and_update_head(
+				&block_view,
+				headers_by_epoch[0].1.next_bps.clone().unwrap()
+			),
+			Err(LightClientError::InvalidNextBlockProducersHash)
+		);
+		assert_eq!(state.head, headers_by_epoch[0].1.clone().into());
+	}
+
 	// #[test]
 	// fn test_can_verify_one_sig() {
 	// 	let prev_hash =
@@ -84,7 +84,19 @@ fn split_key_type_data(
 	}
 }
 
-#[derive(Clone, Eq, Ord, PartialEq, PartialOrd, derive_more::AsRef, derive_more::From)]
+#[derive(
+	Clone,
+	Eq,
+	Ord,
+	PartialEq,
+	PartialOrd,
+	derive_more::AsRef,
+	derive_more::From,
+	codec::Encode,
+	codec::Decode,
+	codec::MaxEncodedLen,
+	scale_info::TypeInfo,
+)]
 #[as_ref(forward)]
 pub struct Secp256K1PublicKey([u8; 64]);
 
@@ -105,7 +117,19 @@ impl core::fmt::Debug for Secp256K1PublicKey {
 	}
 }
 
-#[derive(Clone, Eq, Ord, PartialEq, PartialOrd, derive_more::AsRef, derive_more::From)]
+#[derive(
+	Clone,
+	Eq,
+	Ord,
+	PartialEq,
+	PartialOrd,
+	derive_more::AsRef,
+	derive_more::From,
+	codec::Encode,
+	codec::Decode,
+	codec::MaxEncodedLen,
+	scale_info::TypeInfo,
+)]
 #[as_ref(forward)]
 pub struct ED25519PublicKey(pub [u8; ed25519_dalek::PUBLIC_KEY_LENGTH]);
 
@@ -127,7 +151,19 @@ impl core::fmt::Debug for ED25519PublicKey {
 }
 
 /// Public key container supporting different curves.
-#[derive(Clone, PartialEq, PartialOrd, Ord, Eq)]
+///
+/// The SCALE encoding matches the borsh one: the key type byte followed by the key data.
+#[derive(
+	Clone,
+	PartialEq,
+	PartialOrd,
+	Ord,
+	Eq,
+	codec::Encode,
+	codec::Decode,
+	codec::MaxEncodedLen,
+	scale_info::TypeInfo,
+)]
 pub enum PublicKey {
 	/// 256 bit elliptic curve based public-key.
 	ED25519(ED25519PublicKey),
@@ -448,7 +484,18 @@ const SECP256K1_N_HALF_ONE: U256 =
 
 const SECP256K1_SIGNATURE_LENGTH: usize = 65;
 
-#[derive(Clone, Eq, PartialEq, Hash, derive_more::From, derive_more::Into)]
+#[derive(
+	Clone,
+	Eq,
+	PartialEq,
+	Hash,
+	derive_more::From,
+	derive_more::Into,
+	codec::Encode,
+	codec::Decode,
+	codec::MaxEncodedLen,
+	scale_info::TypeInfo,
+)]
 pub struct Secp256K1Signature([u8; SECP256K1_SIGNATURE_LENGTH]);
 
 impl Secp256K1Signature {
@@ -666,6 +713,73 @@ impl BorshDeserialize for Signature {
 	// }
 }
 
+/// Like borsh, SCALE encodes the key type byte followed by the signature data.
+impl codec::Encode for Signature {
+	fn size_hint(&self) -> usize {
+		match self {
+			Signature::ED25519(_) => 1 + ed25519_dalek::SIGNATURE_LENGTH,
+			Signature::SECP256K1(_) => 1 + SECP256K1_SIGNATURE_LENGTH,
+		}
+	}
+
+	fn encode_to<T: codec::Output + ?Sized>(&self, dest: &mut T) {
+		match self {
+			Signature::ED25519(signature) => {
+				dest.push_byte(0);
+				dest.write(&signature.to_bytes());
+			},
+			Signature::SECP256K1(signature) => {
+				dest.push_byte(1);
+				dest.write(&signature.0);
+			},
+		}
+	}
+}
+
+impl codec::Decode for Signature {
+	fn decode<I: codec::Input>(input: &mut I) -> Result<Self, codec::Error> {
+		match input.read_byte()? {
+			0 => {
+				let array = <[u8; ed25519_dalek::SIGNATURE_LENGTH]>::decode(input)?;
+				ed25519_dalek::Signature::from_bytes(&array)
+					.map(Signature::ED25519)
+					.map_err(|_| "Invalid ED25519 signature".into())
+			},
+			1 => Ok(Signature::SECP256K1(Secp256K1Signature::decode(input)?)),
+			_ => Err("Unknown key type".into()),
+		}
+	}
+}
+
+impl codec::MaxEncodedLen for Signature {
+	fn max_encoded_len() -> usize {
+		1 + ed25519_dalek::SIGNATURE_LENGTH.max(SECP256K1_SIGNATURE_LENGTH)
+	}
+}
+
+impl scale_info::TypeInfo for Signature {
+	type Identity = Self;
+
+	fn type_info() -> scale_info::Type {
+		use scale_info::build::{Fields, Variants};
+
+		scale_info::Type::builder()
+			.path(scale_info::Path::new("Signature", module_path!()))
+			.variant(
+				Variants::new()
+					.variant("ED25519", |v| {
+						v.index(0).fields(
+							Fields::unnamed()
+								.field(|f| f.ty::<[u8; ed25519_dalek::SIGNATURE_LENGTH]>()),
+						)
+					})
+					.variant("SECP256K1", |v| {
+						v.index(1).fields(Fields::unnamed().field(|f| f.ty::<Secp256K1Signature>()))
+					}),
+			)
+	}
+}
+
 impl Display for Signature {
 	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
 		let (key_type, key_data) = match self {
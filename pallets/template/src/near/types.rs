@@ -1,12 +1,179 @@
 use super::dec_format;
 use borsh::{maybestd::string::String, BorshSerialize};
+use core::fmt::{Display, Formatter};
 
-use crate::near::signature::PublicKey;
-/// Account identifier. Provides access to user's state.
+use crate::near::{
+	errors::{ImplicitPublicKeyError, ParseAccountError},
+	signature::{ED25519PublicKey, PublicKey},
+};
 use derive_more::{AsRef as DeriveAsRef, From as DeriveFrom};
 use sp_runtime::sp_std::{prelude::*, vec};
 
-pub type AccountId = String;
+/// The shortest NEAR account id.
+pub const MIN_ACCOUNT_ID_LEN: usize = 2;
+/// The longest NEAR account id.
+pub const MAX_ACCOUNT_ID_LEN: usize = 64;
+
+/// Account identifier. Provides access to user's state.
+///
+/// It is encoded like a string in borsh, serde and SCALE. Decoding checks NEAR's rules: 2 to 64
+/// characters, made of `.` separated parts of lowercase alphanumerics joined by `-` or `_`.
+#[derive(
+	Debug,
+	Clone,
+	PartialEq,
+	Eq,
+	PartialOrd,
+	Ord,
+	Hash,
+	serde::Serialize,
+	serde::Deserialize,
+	BorshSerialize,
+	codec::Encode,
+	scale_info::TypeInfo,
+)]
+#[serde(try_from = "String", into = "String")]
+pub struct AccountId(String);
+
+/// The kinds of NEAR accounts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountType {
+	/// An account named after the hex encoded ED25519 public key that controls it.
+	Implicit,
+	/// A named account without any parent, like `near` or `aurora`.
+	TopLevel,
+	/// A named account created by its parent, like `alice.near`.
+	SubAccount,
+}
+
+impl AccountId {
+	pub fn as_str(&self) -> &str {
+		&self.0
+	}
+
+	/// Checks the NEAR account id rules.
+	pub fn validate(account_id: &str) -> Result<(), ParseAccountError> {
+		if !(MIN_ACCOUNT_ID_LEN..=MAX_ACCOUNT_ID_LEN).contains(&account_id.len()) {
+			return Err(ParseAccountError::InvalidLength { length: account_id.len() })
+		}
+
+		// Whether the previous character was a separator, the start counting as one.
+		let mut after_separator = true;
+		for (index, char) in account_id.chars().enumerate() {
+			match char {
+				'a'..='z' | '0'..='9' => after_separator = false,
+				'-' | '_' | '.' if after_separator =>
+					return Err(ParseAccountError::RedundantSeparator { char, index }),
+				'-' | '_' | '.' => after_separator = true,
+				_ => return Err(ParseAccountError::InvalidChar { char, index }),
+			}
+		}
+		if after_separator {
+			let index = account_id.len() - 1;
+			let char = account_id.as_bytes()[index] as char;
+			return Err(ParseAccountError::RedundantSeparator { char, index })
+		}
+		Ok(())
+	}
+
+	pub fn account_type(&self) -> AccountType {
+		if self.is_implicit() {
+			AccountType::Implicit
+		} else if self.is_top_level() {
+			AccountType::TopLevel
+		} else {
+			AccountType::SubAccount
+		}
+	}
+
+	/// Whether the account is named after a hex encoded ED25519 public key.
+	pub fn is_implicit(&self) -> bool {
+		self.0.len() == 64 && self.0.bytes().all(|b| matches!(b, b'a'..=b'f' | b'0'..=b'9'))
+	}
+
+	/// Whether the account has no parent. Implicit accounts are top-level too.
+	pub fn is_top_level(&self) -> bool {
+		!self.0.contains('.')
+	}
+
+	/// Whether the account was created by `parent`, e.g. `alice.near` by `near`.
+	pub fn is_sub_account_of(&self, parent: &AccountId) -> bool {
+		self.0
+			.strip_suffix(parent.as_str())
+			.and_then(|name| name.strip_suffix('.'))
+			.map_or(false, |name| !name.contains('.'))
+	}
+
+	/// The public key controlling an implicit account.
+	pub fn implicit_public_key(&self) -> Result<PublicKey, ImplicitPublicKeyError> {
+		if !self.is_implicit() {
+			return Err(ImplicitPublicKeyError::AccountIsNotImplicit { account_id: self.clone() })
+		}
+
+		let mut key = [0u8; ed25519_dalek::PUBLIC_KEY_LENGTH];
+		for (byte, hex) in key.iter_mut().zip(self.0.as_bytes().chunks(2)) {
+			let digit = |b: u8| (b as char).to_digit(16).expect("implicit accounts are hex") as u8;
+			*byte = digit(hex[0]) << 4 | digit(hex[1]);
+		}
+		Ok(PublicKey::ED25519(ED25519PublicKey(key)))
+	}
+}
+
+impl TryFrom<String> for AccountId {
+	type Error = ParseAccountError;
+
+	fn try_from(account_id: String) -> Result<Self, Self::Error> {
+		Self::validate(&account_id)?;
+		Ok(Self(account_id))
+	}
+}
+
+impl TryFrom<&str> for AccountId {
+	type Error = ParseAccountError;
+
+	fn try_from(account_id: &str) -> Result<Self, Self::Error> {
+		Self::try_from(String::from(account_id))
+	}
+}
+
+impl core::str::FromStr for AccountId {
+	type Err = ParseAccountError;
+
+	fn from_str(account_id: &str) -> Result<Self, Self::Err> {
+		Self::try_from(account_id)
+	}
+}
+
+impl From<AccountId> for String {
+	fn from(account_id: AccountId) -> Self {
+		account_id.0
+	}
+}
+
+impl AsRef<str> for AccountId {
+	fn as_ref(&self) -> &str {
+		&self.0
+	}
+}
+
+impl Display for AccountId {
+	fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+		f.write_str(&self.0)
+	}
+}
+
+impl codec::Decode for AccountId {
+	fn decode<I: codec::Input>(input: &mut I) -> Result<Self, codec::Error> {
+		Self::try_from(String::decode(input)?).map_err(|_| "Invalid NEAR account id".into())
+	}
+}
+
+impl codec::MaxEncodedLen for AccountId {
+	fn max_encoded_len() -> usize {
+		codec::Compact::<u32>::max_encoded_len() + MAX_ACCOUNT_ID_LEN
+	}
+}
+
 use super::hash::CryptoHash;
 /// Hash used by a struct implementing the Merkle tree.
 pub type MerkleHash = CryptoHash;
@@ -73,3 +240,87 @@ pub enum BlockId {
 	Height(BlockHeight),
 	Hash(CryptoHash),
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::{Decode, Encode};
+
+	#[test]
+	fn test_account_id_successes() {
+		for account_id in [
+			"aa",
+			"near",
+			"alice.near",
+			"relay.aurora",
+			"a-b_c.d-1.near",
+			"0123456789012345678901234567890123456789012345678901234567890123",
+		] {
+			let parsed: AccountId = account_id.parse().unwrap();
+			assert_eq!(parsed.as_str(), account_id);
+
+			let json = format!("\"{}\"", account_id);
+			assert_eq!(parsed, serde_json::from_str::<AccountId>(&json).unwrap());
+			assert_eq!(parsed, AccountId::decode(&mut &parsed.encode()[..]).unwrap());
+		}
+	}
+
+	#[test]
+	fn test_account_id_failures() {
+		for (account_id, want_err) in [
+			("a", ParseAccountError::InvalidLength { length: 1 }),
+			(&"a".repeat(65), ParseAccountError::InvalidLength { length: 65 }),
+			("Alice.near", ParseAccountError::InvalidChar { char: 'A', index: 0 }),
+			("alice near", ParseAccountError::InvalidChar { char: ' ', index: 5 }),
+			("alice@near", ParseAccountError::InvalidChar { char: '@', index: 5 }),
+			(".near", ParseAccountError::RedundantSeparator { char: '.', index: 0 }),
+			("near.", ParseAccountError::RedundantSeparator { char: '.', index: 4 }),
+			("alice..near", ParseAccountError::RedundantSeparator { char: '.', index: 6 }),
+			("alice-_near", ParseAccountError::RedundantSeparator { char: '_', index: 6 }),
+		] {
+			assert_eq!(account_id.parse::<AccountId>(), Err(want_err), "{}", account_id);
+			assert!(serde_json::from_str::<AccountId>(&format!("\"{}\"", account_id)).is_err());
+			assert!(AccountId::decode(&mut &String::from(account_id).encode()[..]).is_err());
+		}
+	}
+
+	#[test]
+	fn test_account_type() {
+		let implicit: AccountId =
+			"98793cd91a3f870fb126f66285808c7e094afcfc4eda8a970f6648cdf0dbd6de"
+				.parse()
+				.unwrap();
+		let near: AccountId = "near".parse().unwrap();
+		let alice: AccountId = "alice.near".parse().unwrap();
+		let bob: AccountId = "bob.alice.near".parse().unwrap();
+
+		assert_eq!(implicit.account_type(), AccountType::Implicit);
+		assert_eq!(near.account_type(), AccountType::TopLevel);
+		assert_eq!(alice.account_type(), AccountType::SubAccount);
+		assert_eq!(bob.account_type(), AccountType::SubAccount);
+
+		assert!(alice.is_sub_account_of(&near));
+		assert!(bob.is_sub_account_of(&alice));
+		assert!(!bob.is_sub_account_of(&near));
+		assert!(!near.is_sub_account_of(&near));
+		assert!(!"alicenear".parse::<AccountId>().unwrap().is_sub_account_of(&near));
+	}
+
+	#[test]
+	fn test_implicit_public_key() {
+		let implicit: AccountId =
+			"98793cd91a3f870fb126f66285808c7e094afcfc4eda8a970f6648cdf0dbd6de"
+				.parse()
+				.unwrap();
+		let PublicKey::ED25519(key) = implicit.implicit_public_key().unwrap() else {
+			panic!("Implicit accounts have ED25519 keys")
+		};
+		assert_eq!(key.0[..4], [0x98, 0x79, 0x3c, 0xd9]);
+		assert_eq!(key.0[31], 0xde);
+
+		assert!(matches!(
+			"alice.near".parse::<AccountId>().unwrap().implicit_public_key(),
+			Err(ImplicitPublicKeyError::AccountIsNotImplicit { .. })
+		));
+	}
+}
@@ -1,17 +1,29 @@
-use crate::near::signature::{ED25519PublicKey, PublicKey, Secp256K1PublicKey, Signature};
+use crate::near::signature::{PublicKey, Signature};
 use borsh::{BorshDeserialize, BorshSerialize};
-use codec::{Decode, Encode};
+use codec::{Decode, Encode, MaxEncodedLen};
+use scale_info::TypeInfo;
 use sp_runtime::sp_std::{prelude::*, vec};
 
 use super::{
 	block_header::BlockHeaderInnerLite,
 	hash::{hash, CryptoHash},
-	merkle::combine_hash,
+	merkle::{combine_hash, verify_hash, MerklePath},
 	serialize::dec_format,
 	types::{AccountId, Balance, BlockHeight},
 };
 
-#[derive(PartialEq, Eq, Debug, Clone, serde::Serialize, serde::Deserialize, BorshSerialize)]
+#[derive(
+	PartialEq,
+	Eq,
+	Debug,
+	Clone,
+	serde::Serialize,
+	serde::Deserialize,
+	BorshSerialize,
+	Encode,
+	Decode,
+	TypeInfo,
+)]
 pub struct LightClientBlockView {
 	pub prev_block_hash: CryptoHash,
 	pub next_block_inner_hash: CryptoHash,
@@ -32,6 +44,7 @@ pub struct LightClientBlockView {
 	BorshDeserialize,
 	codec::Encode,
 	codec::Decode,
+	codec::MaxEncodedLen,
 	scale_info::TypeInfo,
 )]
 pub struct BlockHeaderInnerLiteView {
@@ -71,6 +84,7 @@ impl From<BlockHeaderInnerLite> for BlockHeaderInnerLiteView {
 	Clone,
 	codec::Encode,
 	codec::Decode,
+	codec::MaxEncodedLen,
 	scale_info::TypeInfo,
 	PartialEq,
 	Eq,
@@ -102,10 +116,28 @@ impl LightClientBlockLiteView {
 			&self.prev_block_hash,
 		)
 	}
+
+	/// Whether `block_hash` is part of the block merkle tree committed to by this block, i.e.
+	/// whether it is an ancestor of this block.
+	pub fn includes_block(&self, block_hash: CryptoHash, block_proof: &MerklePath) -> bool {
+		verify_hash(self.inner_lite.block_merkle_root, block_proof, block_hash)
+	}
 }
 
 /// Stores validator and its stake.
-#[derive(BorshSerialize, serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(
+	BorshSerialize,
+	serde::Serialize,
+	serde::Deserialize,
+	Debug,
+	Clone,
+	PartialEq,
+	Eq,
+	Encode,
+	Decode,
+	MaxEncodedLen,
+	TypeInfo,
+)]
 #[serde(tag = "validator_stake_struct_version")]
 pub enum ValidatorStakeView {
 	V1(ValidatorStakeV1),
@@ -137,48 +169,22 @@ impl ValidatorStakeView {
 	}
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, serde::Serialize, serde::Deserialize, BorshSerialize)]
+#[derive(
+	Debug,
+	Clone,
+	Eq,
+	PartialEq,
+	serde::Serialize,
+	serde::Deserialize,
+	BorshSerialize,
+	Encode,
+	Decode,
+	MaxEncodedLen,
+	TypeInfo,
+)]
 pub struct ValidatorStakeV1 {
 	pub account_id: AccountId,
 	pub public_key: PublicKey,
 	#[serde(with = "dec_format")]
 	pub stake: Balance,
 }
-
-impl From<ValidatorStakeViewScaleHax> for ValidatorStakeView {
-	fn from(value: ValidatorStakeViewScaleHax) -> Self {
-		ValidatorStakeView::V1(ValidatorStakeV1 {
-			account_id: borsh::maybestd::string::String::from_utf8(value.account_id).unwrap(),
-			public_key: match value.public_key.len() {
-				32 =>
-					PublicKey::ED25519(ED25519PublicKey::try_from(&value.public_key[..]).unwrap()),
-				64 => PublicKey::SECP256K1(
-					Secp256K1PublicKey::try_from(&value.public_key[..]).unwrap(),
-				),
-				_ => panic!("Invalid public key length"),
-			},
-			stake: value.stake,
-		})
-	}
-}
-
-#[derive(
-	Debug, Clone, Eq, PartialEq, codec::Encode, codec::Decode, scale_info::TypeInfo, BorshSerialize,
-)]
-pub struct ValidatorStakeViewScaleHax {
-	pub account_id: Vec<u8>,
-	pub public_key: Vec<u8>,
-	pub stake: Balance,
-}
-
-impl From<ValidatorStakeView> for ValidatorStakeViewScaleHax {
-	fn from(view: ValidatorStakeView) -> Self {
-		match view {
-			ValidatorStakeView::V1(view) => Self {
-				account_id: view.account_id.into_bytes(),
-				public_key: view.public_key.key_data().to_vec(),
-				stake: view.stake,
-			},
-		}
-	}
-}
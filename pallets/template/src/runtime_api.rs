@@ -0,0 +1,62 @@
+//! Runtime API to read the state of the NEAR light client.
+
+use crate::{
+	near::{
+		errors::ProofError,
+		hash::CryptoHash,
+		proof::RpcLightClientExecutionProofResponse,
+		types::BlockHeight,
+		views::{LightClientBlockLiteView, ValidatorStakeView},
+	},
+	traits::VerifiedOutcome,
+};
+use codec::{Codec, Decode, Encode};
+use sp_runtime::{sp_std::prelude::*, RuntimeDebug};
+
+/// Reasons `NearLightClientApi::verify_execution_proof` rejects a proof.
+#[derive(
+	Encode,
+	Decode,
+	Clone,
+	PartialEq,
+	Eq,
+	RuntimeDebug,
+	scale_info::TypeInfo,
+	thiserror_no_std::Error,
+)]
+pub enum ExecutionProofError {
+	/// The light client is paused.
+	#[error("the light client is paused")]
+	Paused,
+	/// The light client has no trusted head to verify against.
+	#[error("the light client has no trusted head")]
+	NoTrustedHead,
+	/// The outcome was verified already.
+	#[error("the outcome was verified already")]
+	AlreadyVerified,
+	/// The proof does not hold against the current head.
+	#[error("invalid proof: {0}")]
+	InvalidProof(ProofError),
+}
+
+sp_api::decl_runtime_apis! {
+	/// Typed access to the NEAR light client, for frontends and the node.
+	pub trait NearLightClientApi<BlockNumber> where BlockNumber: Codec {
+		/// The trusted head of the light client.
+		fn head() -> Option<LightClientBlockLiteView>;
+		/// The hash of the trusted head.
+		fn head_hash() -> Option<CryptoHash>;
+		/// The height of the trusted head.
+		fn head_height() -> Option<BlockHeight>;
+		/// The timestamp of the trusted head, in nanoseconds.
+		fn head_timestamp() -> Option<u64>;
+		/// The block producers stored for `epoch_id`.
+		fn block_producers(epoch_id: CryptoHash) -> Option<Vec<ValidatorStakeView>>;
+		/// Verify an execution outcome proof against the trusted head, without storing it.
+		fn verify_execution_proof(
+			proof: RpcLightClientExecutionProofResponse,
+		) -> Result<VerifiedOutcome<BlockNumber>, ExecutionProofError>;
+		/// The verified outcome of the transaction or receipt `id`, if any.
+		fn verified_outcome(id: CryptoHash) -> Option<VerifiedOutcome<BlockNumber>>;
+	}
+}
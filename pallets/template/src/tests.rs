@@ -1,10 +1,1308 @@
 use crate::{
 	mock::*,
 	near::{
-		client::{JsonRpcResult, NearRpcResult},
-		views::{LightClientBlockView, ValidatorStakeView, ValidatorStakeViewScaleHax},
-		LightClientState,
+		client::{JsonRpcResult, NearRpcResult, LOCK_BLOCK_EXPIRATION, LOCK_TIMEOUT_EXPIRATION},
+		errors::{LightClientError, ProofError},
+		hash::CryptoHash,
+		proof::TransactionOrReceiptId,
+		views::{LightClientBlockView, ValidatorStakeView},
 	},
-	Error, Event,
+	runtime_api::ExecutionProofError,
+	traits::{NearOutcomeProvider, OutcomeStatus, VerifiedOutcome},
+	BlockProducersByEpoch, BlockViewPayload, Error, Event, ExecutionProofPayload, GenesisConfig,
+	HeadersPayload, LightClientHead, Paused, ProofRequests, RecentHeads, RecentHeights,
+	RelayerMode, Relayers, StoredEpochs,
 };
-use frame_support::{assert_noop, assert_ok};
+use codec::{Decode, Encode};
+use frame_support::{
+	assert_noop, assert_ok,
+	dispatch::GetDispatchInfo,
+	pallet_prelude::*,
+	traits::{GenesisBuild, Hooks, ReservableCurrency},
+	weights::Weight,
+	BoundedVec,
+};
+use sp_core::{
+	sr25519::{Pair, Public, Signature},
+	Pair as _,
+};
+use sp_runtime::{offchain::Duration, traits::ValidateUnsigned, DispatchError, DispatchResult};
+
+fn get_header(file: &str) -> LightClientBlockView {
+	let res: JsonRpcResult = serde_json::from_reader(std::fs::File::open(file).unwrap()).unwrap();
+	if let NearRpcResult::NextBlock(header) = res.result {
+		header
+	} else {
+		panic!("Expected block header")
+	}
+}
+
+fn store_block_producers(epoch_id: crate::near::hash::CryptoHash, block: &LightClientBlockView) {
+	let bps = block.next_bps.clone().unwrap();
+	BlockProducersByEpoch::<Test>::insert(epoch_id, BoundedVec::try_from(bps).unwrap());
+}
+
+fn sign(block_view: LightClientBlockView) -> (BlockViewPayload<Public>, Signature) {
+	let pair = Pair::from_seed(&[1; 32]);
+	let payload = BlockViewPayload { block_view, public: pair.public() };
+	let signature = pair.sign(&payload.encode());
+	(payload, signature)
+}
+
+fn submit(origin: RuntimeOrigin, block_view: LightClientBlockView) -> DispatchResult {
+	let (payload, signature) = sign(block_view);
+	TemplateModule::submit(origin, payload, signature)
+}
+
+fn validate(payload: BlockViewPayload<Public>, signature: Signature) -> TransactionValidity {
+	TemplateModule::validate_unsigned(
+		TransactionSource::External,
+		&crate::Call::submit { payload, signature },
+	)
+}
+
+#[test]
+fn submit_requires_none_origin() {
+	new_test_ext().execute_with(|| {
+		let block = get_header("fixtures/2_previous_epoch.json");
+		assert_noop!(submit(RuntimeOrigin::root(), block), DispatchError::BadOrigin);
+	});
+}
+
+#[test]
+fn submit_requires_trusted_head() {
+	new_test_ext().execute_with(|| {
+		let block = get_header("fixtures/2_previous_epoch.json");
+		assert_noop!(submit(RuntimeOrigin::none(), block), Error::<Test>::NoTrustedHead);
+	});
+}
+
+#[test]
+fn submit_requires_block_producers() {
+	new_test_ext().execute_with(|| {
+		let head = get_header("fixtures/3_previous_epoch.json");
+		LightClientHead::<Test>::put(crate::near::views::LightClientBlockLiteView::from(head));
+
+		let block = get_header("fixtures/2_previous_epoch.json");
+		assert_noop!(submit(RuntimeOrigin::none(), block), Error::<Test>::UnknownBlockProducers);
+	});
+}
+
+#[test]
+fn submit_rejects_malformed_block_view() {
+	let (payload, signature) = sign(get_header("fixtures/2_previous_epoch.json"));
+	let mut call = crate::Call::<Test>::submit { payload, signature }.encode();
+	// Truncate the call within the approvals of the block view.
+	call.truncate(call.len() - 200);
+
+	assert!(crate::Call::<Test>::decode(&mut &call[..]).is_err());
+}
+
+#[test]
+fn submit_rejects_stale_block() {
+	new_test_ext().execute_with(|| {
+		let head = get_header("fixtures/2_previous_epoch.json");
+		store_block_producers(head.inner_lite.epoch_id, &head);
+		LightClientHead::<Test>::put(crate::near::views::LightClientBlockLiteView::from(
+			head.clone(),
+		));
+
+		let block = get_header("fixtures/3_previous_epoch.json");
+		assert_ok!(submit(RuntimeOrigin::none(), block.clone()));
+
+		System::assert_last_event(
+			Event::<Test>::HeaderRejected {
+				height: block.inner_lite.height,
+				reason: LightClientError::BlockAlreadyVerified {
+					height: block.inner_lite.height,
+					head_height: head.inner_lite.height,
+				},
+			}
+			.into(),
+		);
+		assert_eq!(LightClientHead::<Test>::get(), Some(head.into()));
+	});
+}
+
+#[test]
+fn validate_unsigned_rejects_bad_signature() {
+	new_test_ext().execute_with(|| {
+		let head = get_header("fixtures/3_previous_epoch.json");
+		LightClientHead::<Test>::put(crate::near::views::LightClientBlockLiteView::from(head));
+
+		let (payload, _) = sign(get_header("fixtures/2_previous_epoch.json"));
+		let (_, signature) = sign(get_header("fixtures/1_current_epoch.json"));
+		assert_eq!(validate(payload, signature), InvalidTransaction::BadProof.into());
+	});
+}
+
+#[test]
+fn validate_unsigned_rejects_stale_block() {
+	new_test_ext().execute_with(|| {
+		let head = get_header("fixtures/2_previous_epoch.json");
+		LightClientHead::<Test>::put(crate::near::views::LightClientBlockLiteView::from(
+			head.clone(),
+		));
+
+		let (payload, signature) = sign(get_header("fixtures/3_previous_epoch.json"));
+		assert_eq!(validate(payload, signature), InvalidTransaction::Stale.into());
+
+		let (payload, signature) = sign(head);
+		assert_eq!(validate(payload, signature), InvalidTransaction::Stale.into());
+	});
+}
+
+#[test]
+fn validate_unsigned_requires_trusted_head() {
+	new_test_ext().execute_with(|| {
+		let (payload, signature) = sign(get_header("fixtures/2_previous_epoch.json"));
+		assert_eq!(validate(payload, signature), InvalidTransaction::Call.into());
+	});
+}
+
+#[test]
+fn validate_unsigned_provides_height_and_prioritises_advance() {
+	new_test_ext_anchored().execute_with(|| {
+		let head = get_header("fixtures/3_previous_epoch.json");
+
+		let next = get_header("fixtures/2_previous_epoch.json");
+		let (payload, signature) = sign(next.clone());
+		let valid = validate(payload, signature).unwrap();
+		assert_eq!(valid.provides, vec![("NearLightClient", next.inner_lite.height).encode()]);
+		assert_eq!(
+			valid.priority,
+			UnsignedPriority::get() + next.inner_lite.height - head.inner_lite.height
+		);
+		assert!(valid.propagate);
+
+		// Approvals are only verified on dispatch.
+		let mut further = next;
+		further.inner_lite.height += 10;
+		let (payload, signature) = sign(further);
+		assert!(validate(payload, signature).unwrap().priority > valid.priority);
+	});
+}
+
+#[test]
+fn validate_unsigned_rejects_blocks_that_can_not_follow_the_head() {
+	new_test_ext_anchored().execute_with(|| {
+		let invalid_block = InvalidTransaction::Custom(crate::INVALID_BLOCK).into();
+
+		let mut unapproved = get_header("fixtures/2_previous_epoch.json");
+		unapproved.approvals_after_next.iter_mut().for_each(|approval| *approval = None);
+		let (payload, signature) = sign(unapproved.clone());
+		assert_eq!(validate(payload, signature), invalid_block);
+		assert_eq!(validate_headers(vec![unapproved]), invalid_block);
+
+		// Two epochs after the head.
+		let (payload, signature) = sign(get_header("fixtures/1_current_epoch.json"));
+		assert_eq!(validate(payload, signature), invalid_block);
+
+		// The block producers of the head's next epoch are not known.
+		BlockProducersByEpoch::<Test>::remove(
+			get_header("fixtures/3_previous_epoch.json").inner_lite.next_epoch_id,
+		);
+		let (payload, signature) = sign(get_header("fixtures/2_previous_epoch.json"));
+		assert_eq!(validate(payload, signature), invalid_block);
+	});
+}
+
+fn trusted_head_with_proof() -> crate::near::proof::RpcLightClientExecutionProofResponse {
+	let mut head: crate::near::views::LightClientBlockLiteView =
+		get_header("fixtures/2_previous_epoch.json").into();
+	let proof = crate::near::proof::tests::synthetic_proof(&mut head);
+	LightClientHead::<Test>::put(head);
+	proof
+}
+
+#[test]
+fn verify_execution_proof_requires_signed_origin() {
+	new_test_ext().execute_with(|| {
+		let proof = trusted_head_with_proof();
+		assert_noop!(
+			TemplateModule::verify_execution_proof(RuntimeOrigin::none(), proof),
+			DispatchError::BadOrigin
+		);
+	});
+}
+
+#[test]
+fn verify_execution_proof_emits_outcome() {
+	new_test_ext().execute_with(|| {
+		let proof = trusted_head_with_proof();
+		assert_ok!(TemplateModule::verify_execution_proof(
+			RuntimeOrigin::signed(Public::from_raw([1; 32])),
+			proof.clone()
+		));
+
+		System::assert_last_event(
+			Event::<Test>::ExecutionOutcomeVerified {
+				id: proof.outcome_proof.id,
+				block_hash: proof.block_header_lite.hash(),
+				outcome: proof.outcome_proof.outcome,
+			}
+			.into(),
+		);
+	});
+}
+
+#[test]
+fn check_execution_proof_dry_runs() {
+	new_test_ext().execute_with(|| {
+		let proof = trusted_head_with_proof();
+		assert_eq!(
+			TemplateModule::check_execution_proof(&proof),
+			Ok(VerifiedOutcome {
+				executor_id: proof.outcome_proof.outcome.executor_id.clone(),
+				status: OutcomeStatus::SuccessValue(Some(vec![1, 2, 3].try_into().unwrap())),
+				outcome_hash: CryptoHash::hash_borsh(proof.outcome_proof.to_hashes()),
+				block_hash: proof.block_header_lite.hash(),
+				verified_at: System::block_number(),
+			})
+		);
+		assert!(!TemplateModule::is_verified(&proof.outcome_proof.id));
+
+		let mut invalid = proof.clone();
+		invalid.block_proof.clear();
+		assert!(matches!(
+			TemplateModule::check_execution_proof(&invalid),
+			Err(ExecutionProofError::InvalidProof(ProofError::InvalidBlockProof { .. }))
+		));
+
+		Paused::<Test>::put(true);
+		assert_eq!(TemplateModule::check_execution_proof(&proof), Err(ExecutionProofError::Paused));
+	});
+}
+
+#[test]
+fn verify_execution_proof_rejects_invalid_proofs() {
+	new_test_ext().execute_with(|| {
+		let origin = RuntimeOrigin::signed(Public::from_raw([1; 32]));
+
+		let mut proof = trusted_head_with_proof();
+		proof.outcome_proof.outcome.gas_burnt += 1;
+		assert_noop!(
+			TemplateModule::verify_execution_proof(origin.clone(), proof),
+			Error::<Test>::InvalidOutcomeRoot
+		);
+
+		let mut proof = trusted_head_with_proof();
+		proof.block_proof.clear();
+		assert_noop!(
+			TemplateModule::verify_execution_proof(origin, proof),
+			Error::<Test>::InvalidBlockProof
+		);
+	});
+}
+
+#[test]
+fn verified_outcomes_are_recorded() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(7);
+		let proof = trusted_head_with_proof();
+		let id = proof.outcome_proof.id;
+		assert!(!TemplateModule::is_verified(&id));
+
+		assert_ok!(TemplateModule::verify_execution_proof(
+			RuntimeOrigin::signed(Public::from_raw([1; 32])),
+			proof.clone()
+		));
+
+		assert_eq!(
+			TemplateModule::verified_outcome(&id),
+			Some(VerifiedOutcome {
+				executor_id: proof.outcome_proof.outcome.executor_id.clone(),
+				status: OutcomeStatus::SuccessValue(Some(vec![1, 2, 3].try_into().unwrap())),
+				outcome_hash: CryptoHash::hash_borsh(proof.outcome_proof.to_hashes()),
+				block_hash: proof.block_header_lite.hash(),
+				verified_at: 7,
+			})
+		);
+		assert!(TemplateModule::is_verified(&id));
+
+		assert_noop!(
+			TemplateModule::verify_execution_proof(
+				RuntimeOrigin::signed(Public::from_raw([2; 32])),
+				proof
+			),
+			Error::<Test>::OutcomeAlreadyVerified
+		);
+	});
+}
+
+#[test]
+fn execution_proof_weight_grows_with_the_proof() {
+	new_test_ext().execute_with(|| {
+		let proof = trusted_head_with_proof();
+		let weight = TemplateModule::execution_proof_weight(&proof);
+
+		// One more block merkle node, and a log of 10 bytes that is hashed into its own node.
+		let mut longer = proof.clone();
+		longer.block_proof.push(longer.block_proof[0].clone());
+		longer.outcome_proof.outcome.logs.push("0123456789".into());
+		let added =
+			Weight::from_parts(2 * crate::MERKLE_NODE_WEIGHT + 10 * crate::OUTCOME_BYTE_WEIGHT, 0);
+		assert_eq!(TemplateModule::execution_proof_weight(&longer), weight.saturating_add(added));
+
+		let call = crate::Call::<Test>::verify_execution_proof { proof: longer };
+		assert_eq!(call.get_dispatch_info().weight, weight.saturating_add(added));
+	});
+}
+
+#[test]
+fn only_short_outcome_values_are_kept() {
+	use crate::{near::proof::ExecutionStatusView, traits::MAX_OUTCOME_VALUE_LEN};
+
+	let value = vec![7; MAX_OUTCOME_VALUE_LEN as usize];
+	assert_eq!(
+		OutcomeStatus::from(&ExecutionStatusView::SuccessValue(value.clone())),
+		OutcomeStatus::SuccessValue(Some(value.try_into().unwrap()))
+	);
+	let value = vec![7; MAX_OUTCOME_VALUE_LEN as usize + 1];
+	assert_eq!(
+		OutcomeStatus::from(&ExecutionStatusView::SuccessValue(value)),
+		OutcomeStatus::SuccessValue(None)
+	);
+}
+
+#[test]
+fn outcomes_before_missed_chunks_are_verified() {
+	new_test_ext().execute_with(|| {
+		let mut proof = trusted_head_with_proof();
+		proof.outcome_proof.block_hash = CryptoHash::hash_bytes(b"grandparent");
+		assert_ok!(TemplateModule::verify_execution_proof(
+			RuntimeOrigin::signed(Public::from_raw([1; 32])),
+			proof.clone()
+		));
+		assert_eq!(
+			TemplateModule::verified_outcome(&proof.outcome_proof.id).unwrap().block_hash,
+			proof.block_header_lite.hash()
+		);
+	});
+}
+
+#[test]
+fn rpc_backoff_doubles_until_reset() {
+	new_test_ext().execute_with(|| {
+		assert!(!TemplateModule::backing_off(10));
+
+		assert_eq!(TemplateModule::back_off(10), 11);
+		assert!(TemplateModule::backing_off(10));
+		assert!(!TemplateModule::backing_off(11));
+
+		assert_eq!(TemplateModule::back_off(11), 13);
+		assert_eq!(TemplateModule::back_off(13), 17);
+		for _ in 0..10 {
+			TemplateModule::back_off(20);
+		}
+		assert_eq!(TemplateModule::back_off(20), 52);
+
+		TemplateModule::reset_backoff();
+		assert!(!TemplateModule::backing_off(20));
+		assert_eq!(TemplateModule::back_off(20), 21);
+	});
+}
+
+#[test]
+fn sync_lock_excludes_overlapping_runs() {
+	new_test_ext().execute_with(|| {
+		let mut lock = TemplateModule::sync_lock();
+		let guard = lock.try_lock().unwrap();
+		assert!(TemplateModule::sync_lock().try_lock().is_err());
+
+		drop(guard);
+		assert!(TemplateModule::sync_lock().try_lock().is_ok());
+
+		// A lock that is never released expires once both its block and time deadlines pass.
+		let mut lock = TemplateModule::sync_lock();
+		lock.try_lock().unwrap().forget();
+		System::set_block_number(2 + LOCK_BLOCK_EXPIRATION as u64);
+		assert!(TemplateModule::sync_lock().try_lock().is_err());
+		let deadline =
+			sp_io::offchain::timestamp().add(Duration::from_millis(LOCK_TIMEOUT_EXPIRATION + 1));
+		sp_io::offchain::sleep_until(deadline);
+		assert!(TemplateModule::sync_lock().try_lock().is_ok());
+	});
+}
+
+#[test]
+fn submissions_are_in_flight_until_included_or_expired() {
+	new_test_ext().execute_with(|| {
+		assert!(!TemplateModule::submission_in_flight(100, 1));
+
+		TemplateModule::mark_submitted(101, 1);
+		assert!(TemplateModule::submission_in_flight(100, 1));
+		assert!(TemplateModule::submission_in_flight(100, 5));
+		// The head advanced past the submitted block.
+		assert!(!TemplateModule::submission_in_flight(101, 2));
+		// The submitted block dropped out of the pool.
+		assert!(!TemplateModule::submission_in_flight(100, 6));
+	});
+}
+
+fn sign_proof(
+	proof: crate::near::proof::RpcLightClientExecutionProofResponse,
+) -> (ExecutionProofPayload<Public>, Signature) {
+	let pair = Pair::from_seed(&[1; 32]);
+	let payload = ExecutionProofPayload { proof, public: pair.public() };
+	let signature = pair.sign(&payload.encode());
+	(payload, signature)
+}
+
+#[test]
+fn submit_execution_proof_records_outcome() {
+	new_test_ext().execute_with(|| {
+		let proof = trusted_head_with_proof();
+		let id = proof.outcome_proof.id;
+
+		let (payload, signature) = sign_proof(proof.clone());
+		assert_noop!(
+			TemplateModule::submit_execution_proof(
+				RuntimeOrigin::signed(Public::from_raw([1; 32])),
+				payload.clone(),
+				signature.clone()
+			),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(TemplateModule::submit_execution_proof(
+			RuntimeOrigin::none(),
+			payload,
+			signature
+		));
+		assert!(TemplateModule::is_verified(&id));
+	});
+}
+
+#[test]
+fn validate_unsigned_execution_proofs() {
+	new_test_ext().execute_with(|| {
+		let proof = trusted_head_with_proof();
+		let id = proof.outcome_proof.id;
+		let validate = |payload, signature| {
+			TemplateModule::validate_unsigned(
+				TransactionSource::External,
+				&crate::Call::submit_execution_proof { payload, signature },
+			)
+		};
+
+		let (payload, _) = sign_proof(proof.clone());
+		let (_, other_signature) = sign(get_header("fixtures/2_previous_epoch.json"));
+		assert_eq!(validate(payload, other_signature), InvalidTransaction::BadProof.into());
+
+		let (payload, signature) = sign_proof(proof);
+		let valid = validate(payload.clone(), signature.clone()).unwrap();
+		assert_eq!(valid.provides, vec![("NearExecutionProof", id).encode()]);
+
+		assert_ok!(TemplateModule::submit_execution_proof(
+			RuntimeOrigin::none(),
+			payload.clone(),
+			signature.clone()
+		));
+		assert_eq!(validate(payload, signature), InvalidTransaction::Stale.into());
+	});
+}
+
+#[test]
+fn execution_proofs_are_submitted_by_relayers() {
+	new_test_ext().execute_with(|| {
+		let proof = trusted_head_with_proof();
+		assert_ok!(TemplateModule::deregister_relayer(RuntimeOrigin::root(), relayer()));
+
+		let (payload, signature) = sign_proof(proof);
+		assert_eq!(
+			TemplateModule::validate_unsigned(
+				TransactionSource::External,
+				&crate::Call::submit_execution_proof {
+					payload: payload.clone(),
+					signature: signature.clone()
+				},
+			),
+			InvalidTransaction::BadSigner.into()
+		);
+		assert_noop!(
+			TemplateModule::submit_execution_proof(RuntimeOrigin::none(), payload, signature),
+			Error::<Test>::NotRelayer
+		);
+	});
+}
+
+fn receipt(id: CryptoHash) -> TransactionOrReceiptId {
+	TransactionOrReceiptId::Receipt { id, receiver: "alice.near".parse().unwrap() }
+}
+
+/// Request the proof of `id` from `requester()`, with the least deposit.
+fn request(id: CryptoHash) -> DispatchResult {
+	TemplateModule::request_proof(
+		RuntimeOrigin::signed(requester()),
+		receipt(id),
+		Some(ProofRequestDeposit::get()),
+		None,
+	)
+}
+
+#[test]
+fn request_proof_reserves_deposit() {
+	new_test_ext().execute_with(|| {
+		let id = CryptoHash([1; 32]);
+		assert_noop!(
+			TemplateModule::request_proof(RuntimeOrigin::none(), receipt(id), None, None),
+			DispatchError::BadOrigin
+		);
+
+		assert_ok!(request(id));
+		assert_eq!(Balances::reserved_balance(requester()), ProofRequestDeposit::get());
+		assert_eq!(ProofRequests::<Test>::get()[0].id, receipt(id));
+		System::assert_last_event(
+			Event::<Test>::ProofRequested {
+				who: requester(),
+				id,
+				target: requester(),
+				deposit: ProofRequestDeposit::get(),
+			}
+			.into(),
+		);
+
+		assert_noop!(request(id), Error::<Test>::OutcomeAlreadyRequested);
+	});
+}
+
+#[test]
+fn request_proof_queue_is_bounded() {
+	new_test_ext().execute_with(|| {
+		for i in 0..MaxProofRequests::get() as u8 {
+			assert_ok!(request(CryptoHash([i; 32])));
+		}
+		assert_noop!(request(CryptoHash([u8::MAX; 32])), Error::<Test>::TooManyProofRequests);
+	});
+}
+
+#[test]
+fn request_proof_deposit_is_optional() {
+	new_test_ext().execute_with(|| {
+		let poor = Public::from_raw([2; 32]);
+		let deposit = Some(ProofRequestDeposit::get());
+		assert!(TemplateModule::request_proof(
+			RuntimeOrigin::signed(poor),
+			receipt(CryptoHash([1; 32])),
+			deposit,
+			None
+		)
+		.is_err());
+		assert_noop!(
+			TemplateModule::request_proof(
+				RuntimeOrigin::signed(requester()),
+				receipt(CryptoHash([1; 32])),
+				Some(ProofRequestDeposit::get() - 1),
+				None
+			),
+			Error::<Test>::DepositTooLow
+		);
+		assert!(ProofRequests::<Test>::get().is_empty());
+
+		assert_ok!(TemplateModule::request_proof(
+			RuntimeOrigin::signed(poor),
+			receipt(CryptoHash([1; 32])),
+			None,
+			None
+		));
+		assert_eq!(ProofRequests::<Test>::get()[0].deposit, 0);
+		assert_eq!(Balances::reserved_balance(poor), 0);
+	});
+}
+
+#[test]
+fn fulfilled_requests_name_their_target() {
+	new_test_ext().execute_with(|| {
+		let proof = trusted_head_with_proof();
+		let id = proof.outcome_proof.id;
+		let target = Public::from_raw([3; 32]);
+		assert_ok!(TemplateModule::request_proof(
+			RuntimeOrigin::signed(requester()),
+			receipt(id),
+			None,
+			Some(target)
+		));
+		assert_eq!(ProofRequests::<Test>::get()[0].target, target);
+
+		let (payload, signature) = sign_proof(proof);
+		assert_ok!(TemplateModule::submit_execution_proof(
+			RuntimeOrigin::none(),
+			payload,
+			signature
+		));
+		assert!(System::events().iter().any(|record| record.event ==
+			Event::<Test>::ProofRequestFulfilled { who: requester(), id, target }.into()));
+	});
+}
+
+#[test]
+fn verified_requests_are_fulfilled() {
+	new_test_ext().execute_with(|| {
+		let proof = trusted_head_with_proof();
+		let id = proof.outcome_proof.id;
+		assert_ok!(request(id));
+
+		let (payload, signature) = sign_proof(proof);
+		assert_ok!(TemplateModule::submit_execution_proof(
+			RuntimeOrigin::none(),
+			payload,
+			signature
+		));
+		assert!(ProofRequests::<Test>::get().is_empty());
+		assert_eq!(Balances::reserved_balance(requester()), 0);
+		assert!(System::events().iter().any(|record| record.event ==
+			Event::<Test>::ProofRequestFulfilled { who: requester(), id, target: requester() }
+				.into()));
+
+		assert_noop!(request(id), Error::<Test>::OutcomeAlreadyVerified);
+	});
+}
+
+#[test]
+fn proof_requests_expire_in_order() {
+	new_test_ext().execute_with(|| {
+		let first = CryptoHash([1; 32]);
+		let second = CryptoHash([2; 32]);
+		assert_ok!(request(first));
+		System::set_block_number(2);
+		assert_ok!(request(second));
+
+		TemplateModule::on_initialize(ProofRequestTtl::get());
+		assert_eq!(ProofRequests::<Test>::get().len(), 2);
+
+		TemplateModule::on_initialize(1 + ProofRequestTtl::get());
+		assert_eq!(ProofRequests::<Test>::get()[0].id, receipt(second));
+		assert_eq!(Balances::reserved_balance(requester()), ProofRequestDeposit::get());
+		assert_eq!(Balances::free_balance(requester()), ENDOWMENT - 2 * ProofRequestDeposit::get());
+		assert_eq!(Balances::free_balance(reward_pot()), ENDOWMENT + ProofRequestDeposit::get());
+		System::assert_last_event(
+			Event::<Test>::ProofRequestExpired { who: requester(), id: first }.into(),
+		);
+	});
+}
+
+#[test]
+fn proof_requests_take_turns() {
+	new_test_ext().execute_with(|| {
+		let ids: Vec<_> = (1..=3).map(|i| CryptoHash([i; 32])).collect();
+		for id in &ids {
+			assert_ok!(request(*id));
+		}
+
+		let next = || {
+			TemplateModule::next_proof_requests(ProofRequests::<Test>::get().into_inner())
+				.into_iter()
+				.map(|request| request.id.outcome_id())
+				.collect::<Vec<_>>()
+		};
+		assert_eq!(next(), vec![ids[0], ids[1]]);
+		assert_eq!(next(), vec![ids[2], ids[0]]);
+		assert_eq!(next(), vec![ids[1], ids[2]]);
+	});
+}
+
+#[test]
+fn proofs_defer_head_sync_for_one_run() {
+	new_test_ext().execute_with(|| {
+		assert!(!TemplateModule::take_deferred_sync());
+		TemplateModule::defer_sync();
+		assert!(TemplateModule::take_deferred_sync());
+		assert!(!TemplateModule::take_deferred_sync());
+	});
+}
+
+#[test]
+fn genesis_anchors_light_client() {
+	new_test_ext().execute_with(|| {
+		let json = std::fs::read("fixtures/2_previous_epoch.json").unwrap();
+		let config = GenesisConfig::<Test>::from_json_rpc(&json).unwrap();
+		GenesisBuild::<Test>::build(&config);
+
+		let block = get_header("fixtures/2_previous_epoch.json");
+		assert_eq!(LightClientHead::<Test>::get(), Some(block.clone().into()));
+		let block_producers = block.next_bps.unwrap();
+		assert_eq!(
+			BlockProducersByEpoch::<Test>::get(block.inner_lite.next_epoch_id)
+				.unwrap()
+				.into_inner(),
+			block_producers
+		);
+	});
+}
+
+#[test]
+#[should_panic(expected = "Block producers do not match the head's next_bp_hash")]
+fn genesis_rejects_unannounced_block_producers() {
+	new_test_ext().execute_with(|| {
+		let json = std::fs::read("fixtures/2_previous_epoch.json").unwrap();
+		let mut config = GenesisConfig::<Test>::from_json_rpc(&json).unwrap();
+		config.block_producers.pop();
+		GenesisBuild::<Test>::build(&config);
+	});
+}
+
+#[test]
+#[should_panic(expected = "Block producers are not of the head's next epoch")]
+fn genesis_rejects_block_producers_of_other_epochs() {
+	new_test_ext().execute_with(|| {
+		let json = std::fs::read("fixtures/2_previous_epoch.json").unwrap();
+		let mut config = GenesisConfig::<Test>::from_json_rpc(&json).unwrap();
+		config.epoch_id = config.head.as_ref().unwrap().inner_lite.epoch_id;
+		GenesisBuild::<Test>::build(&config);
+	});
+}
+
+fn block_producers(block: &LightClientBlockView) -> Vec<ValidatorStakeView> {
+	block.next_bps.clone().unwrap()
+}
+
+#[test]
+fn reanchor_resets_head_and_block_producers() {
+	new_test_ext().execute_with(|| {
+		let previous: crate::near::views::LightClientBlockLiteView =
+			get_header("fixtures/3_previous_epoch.json").into();
+		LightClientHead::<Test>::put(previous.clone());
+
+		let block = get_header("fixtures/1_current_epoch.json");
+		let head: crate::near::views::LightClientBlockLiteView = block.clone().into();
+		let epoch_id = block.inner_lite.next_epoch_id;
+		assert_noop!(
+			TemplateModule::reanchor(
+				RuntimeOrigin::signed(requester()),
+				head.clone(),
+				epoch_id,
+				block_producers(&block)
+			),
+			DispatchError::BadOrigin
+		);
+
+		assert_ok!(TemplateModule::reanchor(
+			RuntimeOrigin::root(),
+			head.clone(),
+			epoch_id,
+			block_producers(&block)
+		));
+		assert_eq!(LightClientHead::<Test>::get(), Some(head.clone()));
+		assert_eq!(
+			BlockProducersByEpoch::<Test>::get(epoch_id).unwrap().into_inner(),
+			block_producers(&block)
+		);
+		System::assert_last_event(
+			Event::<Test>::Reanchored {
+				previous: Some(previous.hash()),
+				height: head.inner_lite.height,
+				hash: head.hash(),
+			}
+			.into(),
+		);
+	});
+}
+
+#[test]
+fn reanchor_checks_announced_block_producers() {
+	new_test_ext().execute_with(|| {
+		let block = get_header("fixtures/1_current_epoch.json");
+		let mut bps = block_producers(&block);
+		bps.pop();
+		assert_noop!(
+			TemplateModule::reanchor(
+				RuntimeOrigin::root(),
+				block.clone().into(),
+				block.inner_lite.next_epoch_id,
+				bps
+			),
+			Error::<Test>::InvalidNextBlockProducersHash
+		);
+	});
+}
+
+#[test]
+fn reanchor_rejects_block_producers_of_other_epochs() {
+	new_test_ext().execute_with(|| {
+		let block = get_header("fixtures/1_current_epoch.json");
+		assert_noop!(
+			TemplateModule::reanchor(
+				RuntimeOrigin::root(),
+				block.clone().into(),
+				block.inner_lite.epoch_id,
+				block_producers(&block)
+			),
+			Error::<Test>::UnverifiableBlockProducers
+		);
+	});
+}
+
+#[test]
+fn paused_light_client_rejects_blocks_and_proofs() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			TemplateModule::pause(RuntimeOrigin::signed(requester())),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(TemplateModule::pause(RuntimeOrigin::root()));
+		assert!(Paused::<Test>::get());
+		System::assert_last_event(Event::<Test>::LightClientPaused.into());
+
+		let proof = trusted_head_with_proof();
+		assert_noop!(
+			TemplateModule::verify_execution_proof(
+				RuntimeOrigin::signed(requester()),
+				proof.clone()
+			),
+			Error::<Test>::Paused
+		);
+		let (payload, signature) = sign_proof(proof.clone());
+		assert_eq!(
+			TemplateModule::validate_unsigned(
+				TransactionSource::External,
+				&crate::Call::submit_execution_proof { payload, signature },
+			),
+			InvalidTransaction::Call.into()
+		);
+		let block = get_header("fixtures/1_current_epoch.json");
+		assert_noop!(submit(RuntimeOrigin::none(), block), Error::<Test>::Paused);
+
+		assert_ok!(TemplateModule::resume(RuntimeOrigin::root()));
+		System::assert_last_event(Event::<Test>::LightClientResumed.into());
+		assert_ok!(TemplateModule::verify_execution_proof(
+			RuntimeOrigin::signed(requester()),
+			proof
+		));
+	});
+}
+
+#[test]
+fn submit_follows_epoch_transitions() {
+	new_test_ext_anchored().execute_with(|| {
+		for file in ["fixtures/2_previous_epoch.json", "fixtures/1_current_epoch.json"] {
+			let block = get_header(file);
+			assert_ok!(submit(RuntimeOrigin::none(), block.clone()));
+
+			let head: crate::near::views::LightClientBlockLiteView = block.clone().into();
+			System::assert_last_event(
+				Event::<Test>::HeadUpdated { height: block.inner_lite.height, hash: head.hash() }
+					.into(),
+			);
+			assert_eq!(LightClientHead::<Test>::get(), Some(head));
+			assert!(BlockProducersByEpoch::<Test>::contains_key(block.inner_lite.next_epoch_id));
+		}
+	});
+}
+
+#[test]
+fn old_block_producers_are_pruned_on_idle() {
+	new_test_ext().execute_with(|| {
+		let block = get_header("fixtures/2_previous_epoch.json");
+		let epochs: Vec<_> = (0..5u8).map(|i| CryptoHash([i; 32])).collect();
+		for epoch in &epochs {
+			assert_ok!(TemplateModule::submit_bps(
+				RuntimeOrigin::root(),
+				*epoch,
+				block_producers(&block)
+			));
+		}
+		assert_eq!(StoredEpochs::<Test>::get().len(), 5);
+
+		TemplateModule::on_idle(1, Weight::MAX);
+		assert_eq!(StoredEpochs::<Test>::get().into_inner(), epochs[2..].to_vec());
+		assert!(!BlockProducersByEpoch::<Test>::contains_key(epochs[0]));
+		assert!(!BlockProducersByEpoch::<Test>::contains_key(epochs[1]));
+		assert!(epochs[2..].iter().all(BlockProducersByEpoch::<Test>::contains_key));
+	});
+}
+
+#[test]
+fn submit_bps_rejects_too_many_block_producers() {
+	new_test_ext().execute_with(|| {
+		let block = get_header("fixtures/2_previous_epoch.json");
+		let producer = block_producers(&block)[0].clone();
+		let too_many = vec![producer; crate::MAX_BLOCK_PRODUCERS as usize + 1];
+		assert_noop!(
+			TemplateModule::submit_bps(RuntimeOrigin::root(), CryptoHash([0; 32]), too_many),
+			Error::<Test>::TooManyBlockProducers
+		);
+	});
+}
+
+#[test]
+fn recent_heads_are_kept() {
+	new_test_ext_anchored().execute_with(|| {
+		let blocks: Vec<_> = [
+			"fixtures/3_previous_epoch.json",
+			"fixtures/2_previous_epoch.json",
+			"fixtures/1_current_epoch.json",
+		]
+		.into_iter()
+		.map(get_header)
+		.collect();
+		for block in &blocks[1..] {
+			assert_ok!(submit(RuntimeOrigin::none(), block.clone()));
+		}
+
+		// Only the last `HeadHistory` heads are kept.
+		assert_eq!(RecentHeights::<Test>::get().len(), 2);
+		assert!(TemplateModule::recent_head(blocks[0].inner_lite.height).is_none());
+		for block in &blocks[1..] {
+			let head: crate::near::views::LightClientBlockLiteView = block.clone().into();
+			let recent = RecentHeads::<Test>::get(head.inner_lite.height).unwrap();
+			assert_eq!(recent.hash, head.hash());
+			assert_eq!(recent.outcome_root, head.inner_lite.outcome_root);
+			assert_eq!(recent.block_merkle_root, head.inner_lite.block_merkle_root);
+		}
+	});
+}
+
+#[test]
+fn migrates_legacy_block_producers_to_v1() {
+	use crate::migrations::{v1::MigrateToV1, ValidatorStakeViewScaleHax};
+	use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion};
+
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(0).put::<TemplateModule>();
+		let block = get_header("fixtures/1_current_epoch.json");
+		let legacy: Vec<ValidatorStakeViewScaleHax> = block_producers(&block)
+			.into_iter()
+			.map(|view| {
+				let view = view.unwrap_v1();
+				ValidatorStakeViewScaleHax {
+					account_id: view.account_id.as_str().as_bytes().to_vec(),
+					public_key: view.public_key.key_data().to_vec(),
+					stake: view.stake,
+				}
+			})
+			.collect();
+		let epoch_id = block.inner_lite.next_epoch_id;
+		frame_support::storage::unhashed::put(
+			&BlockProducersByEpoch::<Test>::hashed_key_for(epoch_id),
+			&legacy,
+		);
+		let mut malformed = legacy.clone();
+		malformed[0].public_key.clear();
+		let malformed_epoch_id = block.inner_lite.epoch_id;
+		frame_support::storage::unhashed::put(
+			&BlockProducersByEpoch::<Test>::hashed_key_for(malformed_epoch_id),
+			&malformed,
+		);
+
+		MigrateToV1::<Test>::on_runtime_upgrade();
+
+		assert_eq!(TemplateModule::on_chain_storage_version(), StorageVersion::new(1));
+		assert_eq!(
+			BlockProducersByEpoch::<Test>::get(epoch_id).unwrap().into_inner(),
+			block_producers(&block)
+		);
+		assert!(!BlockProducersByEpoch::<Test>::contains_key(malformed_epoch_id));
+	});
+}
+
+#[test]
+fn migration_to_v1_indexes_and_prunes_legacy_epochs() {
+	use crate::migrations::{v1::MigrateToV1, ValidatorStakeViewScaleHax};
+	use frame_support::traits::{OnRuntimeUpgrade, StorageVersion};
+
+	new_test_ext().execute_with(|| {
+		// Storage before the upgrade: block producers of epochs that are not indexed, some of
+		// them older than the head.
+		StorageVersion::new(0).put::<TemplateModule>();
+		let block = get_header("fixtures/1_current_epoch.json");
+		LightClientHead::<Test>::put(crate::near::views::LightClientBlockLiteView::from(
+			block.clone(),
+		));
+		let legacy: Vec<ValidatorStakeViewScaleHax> = block_producers(&block)
+			.into_iter()
+			.map(|view| {
+				let view = view.unwrap_v1();
+				ValidatorStakeViewScaleHax {
+					account_id: view.account_id.as_str().as_bytes().to_vec(),
+					public_key: view.public_key.key_data().to_vec(),
+					stake: view.stake,
+				}
+			})
+			.collect();
+		let old_epochs: Vec<_> = (1..=3).map(|i| CryptoHash([i; 32])).collect();
+		let head_epochs = vec![block.inner_lite.epoch_id, block.inner_lite.next_epoch_id];
+		for epoch_id in old_epochs.iter().chain(&head_epochs) {
+			frame_support::storage::unhashed::put(
+				&BlockProducersByEpoch::<Test>::hashed_key_for(epoch_id),
+				&legacy,
+			);
+		}
+		assert!(StoredEpochs::<Test>::get().is_empty());
+
+		MigrateToV1::<Test>::on_runtime_upgrade();
+
+		// `EpochRetention` is 3: the head's epochs and one of the older ones are kept.
+		let stored = StoredEpochs::<Test>::get().into_inner();
+		assert_eq!(stored.len(), 3);
+		assert_eq!(stored[1..], head_epochs[..]);
+		assert!(old_epochs.contains(&stored[0]));
+		for epoch_id in old_epochs.iter().chain(&head_epochs) {
+			assert_eq!(
+				BlockProducersByEpoch::<Test>::contains_key(epoch_id),
+				stored.contains(epoch_id)
+			);
+		}
+	});
+}
+
+#[test]
+fn relay_rewards_relayers_that_advance_the_head() {
+	use frame_support::dispatch::Pays;
+
+	new_test_ext_anchored().execute_with(|| {
+		let relayer = relayer();
+		let block = get_header("fixtures/2_previous_epoch.json");
+
+		// The block starts a new epoch.
+		let post_info =
+			TemplateModule::relay(RuntimeOrigin::signed(relayer), block.clone()).unwrap();
+		assert_eq!(post_info.pays_fee, Pays::No);
+		let amount = HeaderReward::get() + EpochReward::get();
+		System::assert_last_event(Event::<Test>::RelayerRewarded { who: relayer, amount }.into());
+		assert_eq!(Balances::free_balance(relayer), amount);
+		assert_eq!(Balances::free_balance(reward_pot()), ENDOWMENT - amount);
+
+		// Relaying it again does not advance the head.
+		let post_info = TemplateModule::relay(RuntimeOrigin::signed(relayer), block).unwrap();
+		assert_eq!(post_info.pays_fee, Pays::Yes);
+		assert_eq!(Balances::free_balance(relayer), amount);
+	});
+}
+
+#[test]
+fn relay_advances_the_head_without_a_reward_from_an_empty_pot() {
+	use frame_support::dispatch::Pays;
+
+	new_test_ext_anchored().execute_with(|| {
+		Balances::make_free_balance_be(&reward_pot(), 1);
+		let relayer = relayer();
+		let block = get_header("fixtures/2_previous_epoch.json");
+
+		let post_info =
+			TemplateModule::relay(RuntimeOrigin::signed(relayer), block.clone()).unwrap();
+		assert_eq!(post_info.pays_fee, Pays::No);
+		assert_eq!(LightClientHead::<Test>::get(), Some(block.into()));
+		assert_eq!(Balances::free_balance(relayer), 0);
+	});
+}
+
+#[test]
+fn relayer_mode_is_read_from_local_storage() {
+	use sp_core::offchain::StorageKind;
+
+	new_test_ext().execute_with(|| {
+		assert_eq!(TemplateModule::relayer_mode(), RelayerMode::Unsigned);
+
+		sp_io::offchain::local_storage_set(
+			StorageKind::PERSISTENT,
+			crate::RELAYER_MODE_KEY,
+			b"\"signed\"",
+		);
+		assert_eq!(TemplateModule::relayer_mode(), RelayerMode::Signed);
+
+		sp_io::offchain::local_storage_set(
+			StorageKind::PERSISTENT,
+			crate::RELAYER_MODE_KEY,
+			b"\"bribed\"",
+		);
+		assert_eq!(TemplateModule::relayer_mode(), RelayerMode::Unsigned);
+	});
+}
+
+#[test]
+fn relayers_are_registered_by_admin() {
+	new_test_ext().execute_with(|| {
+		let (alice, bob) = (Public::from_raw([2; 32]), Public::from_raw([3; 32]));
+		assert_noop!(
+			TemplateModule::register_relayer(RuntimeOrigin::signed(alice), alice),
+			DispatchError::BadOrigin
+		);
+
+		assert_ok!(TemplateModule::register_relayer(RuntimeOrigin::root(), alice));
+		System::assert_last_event(Event::<Test>::RelayerRegistered { who: alice }.into());
+		assert_noop!(
+			TemplateModule::register_relayer(RuntimeOrigin::root(), alice),
+			Error::<Test>::AlreadyRelayer
+		);
+		assert_ok!(TemplateModule::register_relayer(RuntimeOrigin::root(), bob));
+		assert_eq!(Relayers::<Test>::get().into_inner(), vec![relayer(), alice, bob]);
+		assert_noop!(
+			TemplateModule::register_relayer(RuntimeOrigin::root(), Public::from_raw([4; 32])),
+			Error::<Test>::TooManyRelayers
+		);
+	});
+}
+
+#[test]
+fn relayers_are_deregistered_by_admin_or_themselves() {
+	new_test_ext().execute_with(|| {
+		let (alice, bob) = (Public::from_raw([2; 32]), Public::from_raw([3; 32]));
+		assert_ok!(TemplateModule::register_relayer(RuntimeOrigin::root(), alice));
+		assert_ok!(TemplateModule::register_relayer(RuntimeOrigin::root(), bob));
+
+		assert_noop!(
+			TemplateModule::deregister_relayer(RuntimeOrigin::signed(alice), bob),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(TemplateModule::deregister_relayer(RuntimeOrigin::signed(bob), bob));
+		System::assert_last_event(Event::<Test>::RelayerDeregistered { who: bob }.into());
+		assert_ok!(TemplateModule::deregister_relayer(RuntimeOrigin::root(), alice));
+		assert_eq!(Relayers::<Test>::get().into_inner(), vec![relayer()]);
+		assert_noop!(
+			TemplateModule::deregister_relayer(RuntimeOrigin::root(), alice),
+			Error::<Test>::NotRelayer
+		);
+	});
+}
+
+#[test]
+fn unregistered_relayers_are_rejected() {
+	new_test_ext_anchored().execute_with(|| {
+		let block = get_header("fixtures/2_previous_epoch.json");
+		assert_noop!(
+			TemplateModule::relay(RuntimeOrigin::signed(Public::from_raw([2; 32])), block.clone()),
+			Error::<Test>::NotRelayer
+		);
+
+		assert_ok!(TemplateModule::deregister_relayer(RuntimeOrigin::root(), relayer()));
+		let (payload, signature) = sign(block.clone());
+		assert_eq!(validate(payload, signature), InvalidTransaction::BadSigner.into());
+		assert_noop!(submit(RuntimeOrigin::none(), block), Error::<Test>::NotRelayer);
+	});
+}
+
+#[test]
+fn relayers_take_turns() {
+	new_test_ext_anchored().execute_with(|| {
+		let alice = Public::from_raw([2; 32]);
+		assert_ok!(TemplateModule::register_relayer(RuntimeOrigin::root(), alice));
+
+		// Turns are `RelayerTurn` blocks long, in registration order.
+		let assigned: Vec<_> = (0..8).map(TemplateModule::assigned_relayer).collect();
+		let (r, a) = (Some(relayer()), Some(alice));
+		assert_eq!(assigned, vec![r, r, a, a, r, r, a, a]);
+
+		System::set_block_number(3);
+		let block = get_header("fixtures/2_previous_epoch.json");
+		let (payload, signature) = sign(block.clone());
+		assert_eq!(
+			validate(payload, signature),
+			InvalidTransaction::Custom(crate::NOT_RELAYER_TURN).into()
+		);
+		assert_noop!(submit(RuntimeOrigin::none(), block.clone()), Error::<Test>::NotRelayerTurn);
+
+		// The relayer of the previous block is still accepted.
+		System::set_block_number(6);
+		assert_ok!(submit(RuntimeOrigin::none(), block.clone()));
+		assert_eq!(LightClientHead::<Test>::get(), Some(block.into()));
+	});
+}
+
+fn sign_headers(block_views: Vec<LightClientBlockView>) -> (HeadersPayload<Public>, Signature) {
+	let pair = Pair::from_seed(&[1; 32]);
+	let payload = HeadersPayload { block_views, public: pair.public() };
+	let signature = pair.sign(&payload.encode());
+	(payload, signature)
+}
+
+fn validate_headers(block_views: Vec<LightClientBlockView>) -> TransactionValidity {
+	let (payload, signature) = sign_headers(block_views);
+	TemplateModule::validate_unsigned(
+		TransactionSource::External,
+		&crate::Call::submit_headers { payload, signature },
+	)
+}
+
+#[test]
+fn block_weight_grows_with_approvals() {
+	new_test_ext().execute_with(|| {
+		let block = get_header("fixtures/2_previous_epoch.json");
+		let mut unapproved = block.clone();
+		unapproved.approvals_after_next.iter_mut().for_each(|approval| *approval = None);
+
+		let weight = TemplateModule::block_view_weight(&block);
+		let signatures = block.approvals_after_next.iter().filter(|a| a.is_some()).count() as u64;
+		assert_eq!(
+			weight,
+			TemplateModule::block_view_weight(&unapproved)
+				.saturating_add(Weight::from_parts(crate::APPROVAL_WEIGHT * signatures, 0))
+		);
+
+		let (payload, signature) = sign(block.clone());
+		let call = crate::Call::<Test>::submit { payload, signature };
+		assert_eq!(call.get_dispatch_info().weight, weight);
+
+		let (payload, signature) = sign_headers(vec![block.clone(), block]);
+		let call = crate::Call::<Test>::submit_headers { payload, signature };
+		assert_eq!(call.get_dispatch_info().weight, weight.saturating_mul(2));
+	});
+}
+
+#[test]
+fn submit_headers_catches_up_across_epochs() {
+	new_test_ext_anchored().execute_with(|| {
+		let blocks = vec![
+			get_header("fixtures/2_previous_epoch.json"),
+			get_header("fixtures/1_current_epoch.json"),
+		];
+		assert!(validate_headers(blocks.clone()).is_ok());
+
+		let (payload, signature) = sign_headers(blocks.clone());
+		assert_ok!(TemplateModule::submit_headers(RuntimeOrigin::none(), payload, signature));
+		let heights: Vec<_> = System::events()
+			.into_iter()
+			.filter_map(|record| match record.event {
+				RuntimeEvent::TemplateModule(Event::HeadUpdated { height, .. }) => Some(height),
+				_ => None,
+			})
+			.collect();
+		assert_eq!(heights, blocks.iter().map(|b| b.inner_lite.height).collect::<Vec<_>>());
+		assert_eq!(LightClientHead::<Test>::get(), Some(blocks[1].clone().into()));
+	});
+}
+
+#[test]
+fn submit_headers_stops_at_the_first_rejected_block() {
+	new_test_ext_anchored().execute_with(|| {
+		let block = get_header("fixtures/2_previous_epoch.json");
+		let blocks =
+			vec![block.clone(), block.clone(), get_header("fixtures/1_current_epoch.json")];
+
+		let (payload, signature) = sign_headers(blocks);
+		assert_ok!(TemplateModule::submit_headers(RuntimeOrigin::none(), payload, signature));
+		System::assert_last_event(
+			Event::<Test>::HeaderRejected {
+				height: block.inner_lite.height,
+				reason: LightClientError::BlockAlreadyVerified {
+					height: block.inner_lite.height,
+					head_height: block.inner_lite.height,
+				},
+			}
+			.into(),
+		);
+		assert_eq!(LightClientHead::<Test>::get(), Some(block.into()));
+	});
+}
+
+#[test]
+fn submit_headers_bounds_the_batch() {
+	new_test_ext_anchored().execute_with(|| {
+		let block = get_header("fixtures/2_previous_epoch.json");
+		let blocks = vec![block; <Test as crate::Config>::MaxHeadersPerBatch::get() as usize + 1];
+
+		assert_eq!(validate_headers(Vec::new()), InvalidTransaction::Call.into());
+		assert_eq!(validate_headers(blocks.clone()), InvalidTransaction::ExhaustsResources.into());
+		let (payload, signature) = sign_headers(blocks);
+		assert_noop!(
+			TemplateModule::submit_headers(RuntimeOrigin::none(), payload, signature),
+			Error::<Test>::TooManyHeaders
+		);
+
+		// A batch is stale as soon as its first block is.
+		let stale = vec![
+			get_header("fixtures/3_previous_epoch.json"),
+			get_header("fixtures/1_current_epoch.json"),
+		];
+		assert_eq!(validate_headers(stale), InvalidTransaction::Stale.into());
+	});
+}
@@ -0,0 +1,90 @@
+use crate::near::{hash::CryptoHash, proof::ExecutionStatusView, types::AccountId};
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::{traits::ConstU32, BoundedVec};
+use sp_runtime::RuntimeDebug;
+
+/// The longest value returned by an outcome that is kept in `VerifiedOutcome`.
+pub const MAX_OUTCOME_VALUE_LEN: u32 = 256;
+
+/// The status of a verified outcome, see `ExecutionStatusView`.
+#[derive(
+	Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, scale_info::TypeInfo, MaxEncodedLen,
+)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub enum OutcomeStatus {
+	/// The execution is pending or unknown.
+	Unknown,
+	/// The execution has failed.
+	Failure,
+	/// The final action succeeded and returned a value. The value is only kept if it is at most
+	/// `MAX_OUTCOME_VALUE_LEN` bytes long, otherwise it has to be checked against the
+	/// `outcome_hash` of the outcome.
+	SuccessValue(Option<BoundedVec<u8, ConstU32<MAX_OUTCOME_VALUE_LEN>>>),
+	/// The final action returned a promise, or the transaction was converted to the receipt
+	/// with this id.
+	SuccessReceiptId(CryptoHash),
+}
+
+impl From<&ExecutionStatusView> for OutcomeStatus {
+	fn from(status: &ExecutionStatusView) -> Self {
+		match status {
+			ExecutionStatusView::Unknown => Self::Unknown,
+			ExecutionStatusView::Failure => Self::Failure,
+			ExecutionStatusView::SuccessValue(value) =>
+				Self::SuccessValue(BoundedVec::try_from(value.clone()).ok()),
+			ExecutionStatusView::SuccessReceiptId(id) => Self::SuccessReceiptId(*id),
+		}
+	}
+}
+
+/// A NEAR transaction or receipt outcome whose inclusion proof was verified.
+#[derive(
+	Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, scale_info::TypeInfo, MaxEncodedLen,
+)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct VerifiedOutcome<BlockNumber> {
+	/// The account the transaction or receipt was executed on.
+	pub executor_id: AccountId,
+	/// The status of the execution.
+	pub status: OutcomeStatus,
+	/// The hash of the outcome, as committed to in the outcome merkle tree. The full outcome, as
+	/// returned by the NEAR RPC, can be checked against it.
+	pub outcome_hash: CryptoHash,
+	/// The NEAR block whose outcome root the outcome was proven in. That is the next block with
+	/// a new chunk in the outcome's shard after the block the outcome was executed in.
+	pub block_hash: CryptoHash,
+	/// The block the proof was verified at.
+	pub verified_at: BlockNumber,
+}
+
+/// Read access to NEAR outcomes verified by the light client, for use by other pallets.
+pub trait NearOutcomeProvider<BlockNumber> {
+	/// The verified outcome of the transaction or receipt `id`, if any.
+	fn verified_outcome(id: &CryptoHash) -> Option<VerifiedOutcome<BlockNumber>>;
+
+	/// Whether the outcome of the transaction or receipt `id` was verified.
+	fn is_verified(id: &CryptoHash) -> bool {
+		Self::verified_outcome(id).is_some()
+	}
+}
+
+/// Hook for the outcomes requested with `request_proof` once they are verified.
+pub trait OnOutcomeVerified<AccountId, BlockNumber> {
+	/// The outcome `id` requested by `requester` for `target` was verified.
+	fn on_outcome_verified(
+		requester: &AccountId,
+		target: &AccountId,
+		id: &CryptoHash,
+		outcome: &VerifiedOutcome<BlockNumber>,
+	);
+}
+
+impl<AccountId, BlockNumber> OnOutcomeVerified<AccountId, BlockNumber> for () {
+	fn on_outcome_verified(
+		_requester: &AccountId,
+		_target: &AccountId,
+		_id: &CryptoHash,
+		_outcome: &VerifiedOutcome<BlockNumber>,
+	) {
+	}
+}
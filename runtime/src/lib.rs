@@ -16,9 +16,10 @@ use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
 use sp_runtime::{
 	create_runtime_str, generic, impl_opaque_keys,
 	traits::{
-		AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount, NumberFor, One, Verify,
+		AccountIdConversion, AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount,
+		NumberFor, One, Verify,
 	},
-	transaction_validity::{TransactionSource, TransactionValidity},
+	transaction_validity::{TransactionPriority, TransactionSource, TransactionValidity},
 	ApplyExtrinsicResult, MultiSignature, SaturatedConversion,
 };
 use sp_std::prelude::*;
@@ -38,7 +39,7 @@ pub use frame_support::{
 		},
 		IdentityFee, Weight,
 	},
-	StorageValue,
+	PalletId, StorageValue,
 };
 pub use frame_system::Call as SystemCall;
 pub use pallet_balances::Call as BalancesCall;
@@ -267,11 +268,41 @@ impl pallet_sudo::Config for Runtime {
 	type RuntimeCall = RuntimeCall;
 }
 
+parameter_types! {
+	pub const NearUnsignedPriority: TransactionPriority = TransactionPriority::max_value() / 2;
+	pub const NearRpcEndpoints: &'static [&'static str] =
+		&[pallet_template::near::client::NEAR_RPC_ENDPOINT];
+	pub const NearArchivalEndpoints: &'static [&'static str] =
+		&[pallet_template::near::client::NEAR_RPC_ARCHIVE_ENDPOINT];
+	pub const NearProofRequestDeposit: Balance = 100 * EXISTENTIAL_DEPOSIT;
+	pub NearRewardPot: AccountId = PalletId(*b"near/rwd").into_account_truncating();
+	pub const NearHeaderReward: Balance = 10 * EXISTENTIAL_DEPOSIT;
+	pub const NearEpochReward: Balance = 100 * EXISTENTIAL_DEPOSIT;
+}
+
 /// Configure the pallet-template in pallets/template.
 impl pallet_template::Config for Runtime {
 	type Call = RuntimeCall;
 	type RuntimeEvent = RuntimeEvent;
 	type AuthorityId = pallet_template::crypto::TestAuthId;
+	type UnsignedPriority = NearUnsignedPriority;
+	type RpcEndpoints = NearRpcEndpoints;
+	type ArchivalEndpoints = NearArchivalEndpoints;
+	type Currency = Balances;
+	type ProofRequestDeposit = NearProofRequestDeposit;
+	type MaxProofRequests = ConstU32<256>;
+	type ProofRequestsPerBlock = ConstU32<4>;
+	type ProofRequestTtl = ConstU32<{ 24 * HOURS }>;
+	type OnOutcomeVerified = ();
+	type AdminOrigin = frame_system::EnsureRoot<AccountId>;
+	type EpochRetention = ConstU32<4>;
+	type HeadHistory = ConstU32<256>;
+	type RewardPot = NearRewardPot;
+	type HeaderReward = NearHeaderReward;
+	type EpochReward = NearEpochReward;
+	type MaxRelayers = ConstU32<16>;
+	type RelayerTurn = ConstU32<{ 10 * MINUTES }>;
+	type MaxHeadersPerBatch = ConstU32<16>;
 }
 
 impl frame_system::offchain::SigningTypes for Runtime {
@@ -378,8 +409,12 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPalletsWithSystem,
+	Migrations,
 >;
 
+/// Storage migrations run on runtime upgrade.
+type Migrations = (pallet_template::migrations::v1::MigrateToV1<Runtime>,);
+
 #[cfg(feature = "runtime-benchmarks")]
 #[macro_use]
 extern crate frame_benchmarking;
@@ -555,6 +590,46 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_template::runtime_api::NearLightClientApi<Block, BlockNumber> for Runtime {
+		fn head() -> Option<pallet_template::near::views::LightClientBlockLiteView> {
+			TemplateModule::light_client_head()
+		}
+
+		fn head_hash() -> Option<pallet_template::near::hash::CryptoHash> {
+			TemplateModule::light_client_head().map(|head| head.hash())
+		}
+
+		fn head_height() -> Option<pallet_template::near::types::BlockHeight> {
+			TemplateModule::light_client_head().map(|head| head.inner_lite.height)
+		}
+
+		fn head_timestamp() -> Option<u64> {
+			TemplateModule::light_client_head().map(|head| head.inner_lite.timestamp_nanosec)
+		}
+
+		fn block_producers(
+			epoch_id: pallet_template::near::hash::CryptoHash,
+		) -> Option<Vec<pallet_template::near::views::ValidatorStakeView>> {
+			TemplateModule::epoch_block_producers(&epoch_id)
+		}
+
+		fn verify_execution_proof(
+			proof: pallet_template::near::proof::RpcLightClientExecutionProofResponse,
+		) -> Result<
+			pallet_template::traits::VerifiedOutcome<BlockNumber>,
+			pallet_template::runtime_api::ExecutionProofError,
+		> {
+			TemplateModule::check_execution_proof(&proof)
+		}
+
+		fn verified_outcome(
+			id: pallet_template::near::hash::CryptoHash,
+		) -> Option<pallet_template::traits::VerifiedOutcome<BlockNumber>> {
+			use pallet_template::traits::NearOutcomeProvider;
+			TemplateModule::verified_outcome(&id)
+		}
+	}
+
 
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {